@@ -0,0 +1,162 @@
+//! Solves every `tests/valid_*.fillit` input and reports the time it takes.
+//!
+//! ```text
+//! cargo bench --bench corpus -- [--hard] [--order <order>] [--flood-fill]
+//!     [--no-unreachable-pruning] [--cache <MiB>] [--save-baseline <name>]
+//!     [--baseline <name>] [filter]
+//! ```
+//!
+//! The `valid_hard_forum*` inputs are only solved with `--hard`. Baselines are saved
+//! under `fillit-bench/` in the target directory of cargo and a run compares itself
+//! to one with `--baseline`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use std::{env, fs};
+
+use anyhow::{bail, Context};
+use fillit::{find_best_fit_with, parse_tetriminos, SearchOptions};
+
+/// Every input is solved at least this number of times.
+const MIN_RUNS: usize = 3;
+/// An input is solved again until this amount of time is spent on it.
+const TARGET_TIME: Duration = Duration::from_secs(1);
+
+struct Args {
+    hard: bool,
+    options: SearchOptions,
+    save_baseline: Option<String>,
+    baseline: Option<String>,
+    filter: Option<String>,
+}
+
+fn parse_args() -> anyhow::Result<Args> {
+    let mut parsed = Args {
+        hard: false,
+        options: SearchOptions::default(),
+        save_baseline: None,
+        baseline: None,
+        filter: None,
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            // Given by cargo bench.
+            "--bench" => (),
+            "--hard" => parsed.hard = true,
+            "--flood-fill" => parsed.options.flood_fill = true,
+            "--no-unreachable-pruning" => parsed.options.unreachable_pruning = false,
+            "--order" => parsed.options.order = args.next().context("missing order")?.parse()?,
+            "--cache" => {
+                let mebibytes: usize = args.next().context("missing cache size")?.parse()?;
+                parsed.options.cache_memory = Some(mebibytes * 1024 * 1024);
+            }
+            "--save-baseline" => parsed.save_baseline = args.next(),
+            "--baseline" => parsed.baseline = args.next(),
+            otherwise if otherwise.starts_with("--") => bail!("unknown argument {}", otherwise),
+            otherwise => parsed.filter = Some(otherwise.to_string()),
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn inputs(hard: bool, filter: Option<&str>) -> anyhow::Result<Vec<PathBuf>> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if !name.starts_with("valid_") || !name.ends_with(".fillit") {
+            continue;
+        }
+        if name.starts_with("valid_hard_forum") && !hard {
+            continue;
+        }
+        if filter.is_some_and(|f| !name.contains(f)) {
+            continue;
+        }
+        paths.push(path);
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// The baselines are saved in the target directory of cargo, `CARGO_TARGET_DIR` or `target`.
+fn baseline_path(name: &str) -> PathBuf {
+    let target = env::var_os("CARGO_TARGET_DIR").unwrap_or_else(|| "target".into());
+    PathBuf::from(target).join("fillit-bench").join(name).with_extension("tsv")
+}
+
+fn read_baseline(name: &str) -> anyhow::Result<BTreeMap<String, Duration>> {
+    let path = baseline_path(name);
+    let text = fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+    let mut baseline = BTreeMap::new();
+    for line in text.lines() {
+        if let Some((name, nanos)) = line.split_once('\t') {
+            baseline.insert(name.to_string(), Duration::from_nanos(nanos.parse()?));
+        }
+    }
+    Ok(baseline)
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = parse_args()?;
+    let baseline = args.baseline.as_deref().map(read_baseline).transpose()?;
+
+    let mut medians = BTreeMap::new();
+    let mut by_size: BTreeMap<usize, (usize, Duration)> = BTreeMap::new();
+
+    println!("{:<28} {:>4} {:>6} {:>12} {:>10}", "input", "size", "runs", "median", "change");
+    for path in inputs(args.hard, args.filter.as_deref())? {
+        let name = path.file_stem().and_then(|n| n.to_str()).unwrap_or_default().to_string();
+        let tetriminos = parse_tetriminos(&fs::read_to_string(&path)?)?;
+
+        let mut times = Vec::new();
+        let mut size = 0;
+        let started = Instant::now();
+        while times.len() < MIN_RUNS || started.elapsed() < TARGET_TIME {
+            let before = Instant::now();
+            let (map, _stats) = find_best_fit_with(&tetriminos, &args.options).unwrap();
+            times.push(before.elapsed());
+            size = map.size();
+        }
+        times.sort();
+        let median = times[times.len() / 2];
+
+        let change = match baseline.as_ref().and_then(|b| b.get(&name)) {
+            Some(old) => {
+                let ratio = median.as_secs_f64() / old.as_secs_f64();
+                format!("{:+.1}%", (ratio - 1.0) * 100.0)
+            }
+            None => String::new(),
+        };
+        println!("{:<28} {:>4} {:>6} {:>12?} {:>10}", name, size, times.len(), median, change);
+
+        let entry = by_size.entry(size).or_default();
+        entry.0 += 1;
+        entry.1 += median;
+        medians.insert(name, median);
+    }
+
+    println!();
+    println!("{:<28} {:>4} {:>6} {:>12}", "board size", "", "inputs", "total");
+    for (size, (count, total)) in &by_size {
+        println!("{:<28} {:>4} {:>6} {:>12?}", format!("{0}x{0}", size), "", count, total);
+    }
+
+    if let Some(name) = &args.save_baseline {
+        let path = baseline_path(name);
+        fs::create_dir_all(path.parent().unwrap())?;
+        let text: String = medians
+            .iter()
+            .map(|(name, median)| format!("{}\t{}\n", name, median.as_nanos()))
+            .collect();
+        fs::write(&path, text)?;
+        println!("\nsaved the baseline to {}", path.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "ffi")]
+    generate_header();
+}
+
+/// Writes the C header of the functions exported by `src/ffi.rs`.
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    cbindgen::generate(&crate_dir)
+        .expect("unable to generate the C header")
+        .write_to_file(std::path::Path::new(&crate_dir).join("include/fillit.h"));
+}
@@ -0,0 +1,89 @@
+/* Tests the C interface, built and run by `ffi/test.sh`. */
+#include <assert.h>
+#include <stdio.h>
+
+#include "fillit.h"
+
+/* The tests/valid_1.fillit map. */
+static const char *VALID_1 =
+    "....\n.##.\n..#.\n..#.\n\n"
+    "....\n####\n....\n....\n\n"
+    "#...\n###.\n....\n....\n\n"
+    "....\n##..\n.##.\n....\n";
+
+/* The tests/answers/valid_1.answer map: L180, I90, J270 and Z. */
+static const FillitPlacement ANSWER_1[] = {
+    {'A', 0, 2, 5},
+    {'B', 3, 0, 1},
+    {'C', 1, 0, 10},
+    {'D', 0, 0, 13},
+};
+
+static void test_solve(void)
+{
+    FillitMap *tetriminos = NULL;
+    assert(fillit_parse(VALID_1, true, &tetriminos) == FILLIT_OK);
+    assert(tetriminos->size == 0);
+    assert(tetriminos->count == 4);
+    for (size_t i = 0; i < tetriminos->count; i++) {
+        assert(tetriminos->placements[i].letter == ANSWER_1[i].letter);
+        assert(tetriminos->placements[i].variant == ANSWER_1[i].variant);
+    }
+
+    FillitMap *solution = NULL;
+    assert(fillit_solve(tetriminos, 0, &solution) == FILLIT_OK);
+    assert(solution->size == 4);
+    assert(solution->count == 4);
+    for (size_t i = 0; i < solution->count; i++) {
+        const FillitPlacement *p = &solution->placements[i];
+        assert(p->letter == ANSWER_1[i].letter);
+        assert(p->row == ANSWER_1[i].row);
+        assert(p->col == ANSWER_1[i].col);
+        assert(p->variant == ANSWER_1[i].variant);
+    }
+
+    /* A solution can be solved again, only the variants are read. */
+    FillitMap *again = NULL;
+    assert(fillit_solve(solution, 1000, &again) == FILLIT_OK);
+    assert(again->size == 4);
+
+    fillit_free(again);
+    fillit_free(solution);
+    fillit_free(tetriminos);
+}
+
+static void test_errors(void)
+{
+    FillitMap *map = NULL;
+    assert(fillit_parse("..X.\n..#.\n.##.\n....\n", false, &map) == FILLIT_INVALID_CHARACTER);
+    assert(fillit_parse("...\n..#.\n.##.\n.#..\n", false, &map) == FILLIT_LINE_TOO_SHORT);
+    assert(fillit_parse("#...\n#...\n#...\n#...", true, &map) == FILLIT_MISSING_NEWLINE);
+    assert(fillit_parse("#...\n.#..\n..#.\n...#\n", false, &map) == FILLIT_INVALID_TETRIMINO);
+    assert(fillit_parse("", false, &map) == FILLIT_NO_TETRIMINOS);
+    assert(fillit_parse("\xff", false, &map) == FILLIT_INVALID_UTF8);
+    assert(fillit_parse(NULL, false, &map) == FILLIT_NULL_POINTER);
+    assert(map == NULL);
+
+    FillitPlacement placement = {'A', 0, 0, 19};
+    FillitMap invalid = {0, 1, &placement};
+    assert(fillit_solve(&invalid, 0, &map) == FILLIT_INVALID_VARIANT);
+
+    /* A map built by hand can hold more tetriminos than the 26 of any solution. */
+    FillitPlacement placements[27] = {{0}};
+    FillitMap too_many = {0, 27, placements};
+    assert(fillit_solve(&too_many, 0, &map) == FILLIT_TOO_MANY_TETRIMINOS);
+    FillitMap dangling = {0, 1, NULL};
+    assert(fillit_solve(&dangling, 0, &map) == FILLIT_NULL_POINTER);
+    assert(fillit_solve(NULL, 0, &map) == FILLIT_NULL_POINTER);
+    assert(map == NULL);
+
+    fillit_free(NULL);
+}
+
+int main(void)
+{
+    test_solve();
+    test_errors();
+    printf("ok\n");
+    return 0;
+}
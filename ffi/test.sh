@@ -0,0 +1,8 @@
+#!/bin/sh
+# Builds the static library with its C header and runs the C tests against it.
+set -e
+
+cargo build --release --features ffi
+cc -Wall -Wextra -Werror -std=c99 -Iinclude ffi/test.c target/release/libfillit.a \
+    -lpthread -ldl -lm -o target/release/fillit-ffi-test
+./target/release/fillit-ffi-test
@@ -0,0 +1,12 @@
+#![no_main]
+
+use fillit::{parse_tetriminos_with, ParseMode, ParseOptions};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        for mode in [ParseMode::Strict, ParseMode::Lenient] {
+            let _ = parse_tetriminos_with(text, &ParseOptions { mode, ..ParseOptions::default() });
+        }
+    }
+});
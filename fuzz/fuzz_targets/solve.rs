@@ -0,0 +1,27 @@
+#![no_main]
+
+use fillit::{find_best_fit_with, parse_tetriminos, SearchOptions, SearchOrder};
+use libfuzzer_sys::fuzz_target;
+
+/// Bigger inputs take too long to solve for the fuzzer to make progress.
+const MAX_TETRIMINOS: usize = 8;
+
+fuzz_target!(|data: &[u8]| {
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => text,
+        Err(_) => return,
+    };
+
+    let tetriminos = match parse_tetriminos(text) {
+        Ok(tetriminos) if tetriminos.len() <= MAX_TETRIMINOS => tetriminos,
+        _ => return,
+    };
+
+    for order in
+        [SearchOrder::Lexicographic, SearchOrder::AnyMinimal, SearchOrder::MostConstrainedCell]
+    {
+        let options = SearchOptions { order, ..SearchOptions::default() };
+        let (map, _stats) = find_best_fit_with(&tetriminos, &options).unwrap();
+        assert_eq!(map.tetriminos().len(), tetriminos.len());
+    }
+});
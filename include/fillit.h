@@ -0,0 +1,114 @@
+#ifndef FILLIT_H
+#define FILLIT_H
+
+/* Generated by cbindgen from src/ffi.rs when building with the `ffi` feature. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The result of every function, the parse errors have the codes from 1 to 99.
+ */
+typedef enum FillitStatus {
+  FILLIT_OK = 0,
+  FILLIT_INVALID_CHARACTER = 1,
+  FILLIT_LINE_TOO_LONG = 2,
+  FILLIT_LINE_TOO_SHORT = 3,
+  FILLIT_TOO_MANY_LINES = 4,
+  FILLIT_TOO_FEW_LINES = 5,
+  FILLIT_INVALID_TETRIMINO = 6,
+  FILLIT_EMPTY_TETRIMINO = 7,
+  FILLIT_UNKNOWN_PIECE = 8,
+  FILLIT_INVALID_JSON = 9,
+  FILLIT_MISSING_NEWLINE = 10,
+  FILLIT_NO_TETRIMINOS = 11,
+  FILLIT_TOO_MANY_TETRIMINOS = 12,
+  /**
+   * One of the pointers given is null.
+   */
+  FILLIT_NULL_POINTER = 100,
+  /**
+   * The text is not valid UTF-8.
+   */
+  FILLIT_INVALID_UTF8 = 101,
+  /**
+   * A tetrimino variant is not one of the known ones.
+   */
+  FILLIT_INVALID_VARIANT = 102,
+  /**
+   * The search took longer than the timeout.
+   */
+  FILLIT_TIMED_OUT = 103,
+} FillitStatus;
+
+/**
+ * A tetrimino, its letter in the answers and where it is written.
+ */
+typedef struct FillitPlacement {
+  char letter;
+  uint8_t row;
+  uint8_t col;
+  /**
+   * The index of the shape in the `Tetrimino` enum, named in order I, I90, O,
+   * L, L90, L180, L270, J, J90, J180, J270, S, S90, Z, Z90, T, T90, T180 and T270.
+   */
+  uint8_t variant;
+} FillitPlacement;
+
+/**
+ * The tetriminos in the input order, their positions are only meaningful once solved.
+ */
+typedef struct FillitMap {
+  /**
+   * The side of the map, 0 when it is not solved.
+   */
+  size_t size;
+  size_t count;
+  struct FillitPlacement *placements;
+} FillitMap;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Parses the 42 subject format, strictly or leniently, and writes
+ * the unsolved tetriminos in `map` when the status is `FillitOk`.
+ *
+ * # Safety
+ *
+ * `text` must be a NUL-terminated string and `map` a valid pointer.
+ */
+enum FillitStatus fillit_parse(const char *text, bool strict, struct FillitMap **map);
+
+/**
+ * Finds the lexicographic best fit of the tetriminos and writes it in `solution`
+ * when the status is `FillitOk`, the search gives up after `timeout_ms` unless it is 0.
+ *
+ * # Safety
+ *
+ * `tetriminos` must come from `fillit_parse` or `fillit_solve`, or be built by hand with
+ * `placements` pointing to `count` placements, and `solution` must be a valid pointer.
+ * Only the variants are read, a map of more than 26 tetriminos is rejected.
+ */
+enum FillitStatus fillit_solve(const struct FillitMap *tetriminos,
+                               uint32_t timeout_ms,
+                               struct FillitMap **solution);
+
+/**
+ * Frees a map returned by `fillit_parse` or `fillit_solve`, null is ignored.
+ *
+ * # Safety
+ *
+ * `map` must not be used after this call.
+ */
+void fillit_free(struct FillitMap *map);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* FILLIT_H */
@@ -0,0 +1,10 @@
+#!/bin/sh
+# Builds the Python module in a virtual environment and runs its tests,
+# the environment is kept in `target/venv` between runs.
+set -e
+
+[ -d target/venv ] || python3 -m venv target/venv
+. target/venv/bin/activate
+pip install --quiet maturin pytest
+maturin develop --release
+pytest python
@@ -0,0 +1,78 @@
+"""Tests the Python module, built and run by `python/test.sh`."""
+
+import threading
+import time
+from pathlib import Path
+
+import pytest
+
+import fillit
+from fillit import Tetrimino
+
+TESTS = Path(__file__).parent.parent / "tests"
+
+
+def read(name):
+    return (TESTS / name).read_text()
+
+
+def test_tetrimino():
+    t = Tetrimino.from_name("L90")
+    assert t == Tetrimino.NormalLRotate90
+    assert t.name == "L90"
+    assert t.size() == (2, 3)
+    assert t.jump_columns() == 3
+    assert t.boolean_map()[0] == [False, False, True, False]
+    assert str(t) == "..#.\n###.\n....\n....\n"
+    assert len({Tetrimino.Square, Tetrimino.from_name("O")}) == 1
+    with pytest.raises(ValueError):
+        Tetrimino.from_name("X")
+
+
+def test_parse():
+    tetriminos = fillit.parse_tetriminos(read("valid_1.fillit"), mode="strict")
+    assert [t.name for t in tetriminos] == ["L180", "I90", "J270", "Z"]
+    with pytest.raises(ValueError, match="invalid tetrimino character"):
+        fillit.parse_tetriminos(read("not_valid_comma.fillit"))
+    with pytest.raises(ValueError, match="unknown parse mode"):
+        fillit.parse_tetriminos("", mode="loose")
+
+
+def test_find_best_fit():
+    tetriminos = fillit.parse_tetriminos(read("valid_1.fillit"))
+    solution, stats = fillit.find_best_fit(tetriminos)
+    assert str(solution) == read("answers/valid_1.answer")
+    assert solution.size == 4
+    assert solution.placements[0] == ("A", Tetrimino.NormalLRotate180, 0, 2)
+    assert stats.placements > 0
+
+    solution, _stats = fillit.find_best_fit(tetriminos, order="any-minimal", flood_fill=True)
+    assert solution.size == 4
+
+
+def test_find_fits():
+    tetriminos = fillit.parse_tetriminos(read("valid_1.fillit"))
+    best, _stats = fillit.find_best_fit(tetriminos)
+    fits, _stats = fillit.find_fits(tetriminos, 3)
+    assert 1 <= len(fits) <= 3
+    assert str(fits[0]) == str(best)
+    assert len({str(fit) for fit in fits}) == len(fits)
+    assert all(fit.size == best.size for fit in fits)
+
+
+def test_timeout_releases_the_gil():
+    tetriminos = fillit.parse_tetriminos(read("valid_hard_forum_23.fillit"))
+    ticks = []
+
+    def tick():
+        while len(ticks) < 5:
+            ticks.append(time.monotonic())
+            time.sleep(0.01)
+
+    ticker = threading.Thread(target=tick)
+    ticker.start()
+    with pytest.raises(TimeoutError):
+        fillit.find_best_fit(tetriminos, timeout=0.2)
+    end = time.monotonic()
+    ticker.join()
+    assert sum(t < end for t in ticks) >= 3
@@ -0,0 +1,41 @@
+use anyhow::{ensure, Context};
+
+use crate::{ensure_count, ParseError, Position, Tetrimino};
+
+/// Reads back the tetriminos of a solved map, like the ones in `tests/answers`,
+/// in the order of their letters, the empty tiles use the given character.
+pub fn parse_answer(text: &str, empty: char) -> anyhow::Result<Vec<Tetrimino>> {
+    let mut letters: Vec<Vec<Position>> = Vec::new();
+    // The empty tiles may be spaces, only the line terminators are stripped.
+    for (row, line) in text.lines().filter(|l| !l.is_empty()).enumerate() {
+        for (col, c) in line.chars().enumerate().filter(|(_, c)| *c != empty) {
+            ensure!(c.is_ascii_uppercase(), ParseError::InvalidCharacter(c));
+            let letter = (c as u8 - b'A') as usize;
+            if letters.len() <= letter {
+                letters.resize(letter + 1, Vec::new());
+            }
+            letters[letter].push(Position { col, row });
+        }
+    }
+
+    let tetriminos = letters
+        .iter()
+        .enumerate()
+        .map(|(i, tiles)| parse_piece(tiles).with_context(|| format!("number {}", i)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    ensure_count(&tetriminos)?;
+    Ok(tetriminos)
+}
+
+fn parse_piece(tiles: &[Position]) -> anyhow::Result<Tetrimino> {
+    let top = tiles.iter().map(|p| p.row).min().unwrap_or_default();
+    let left = tiles.iter().map(|p| p.col).min().unwrap_or_default();
+
+    let mut buffer = [[false; 4]; 4];
+    for p in tiles {
+        let tile = buffer.get_mut(p.row - top).and_then(|line| line.get_mut(p.col - left));
+        *tile.ok_or(ParseError::InvalidTetrimino)? = true;
+    }
+    Tetrimino::from_boolean_map(buffer)
+}
@@ -0,0 +1,179 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use std::{fs, thread};
+
+use anyhow::{bail, Context};
+use fillit::{find_best_fit_with, SearchError, SearchOptions};
+
+use crate::{parse_search_arg, Input};
+
+/// Every file is given this time to be solved, unless `--timeout` says otherwise.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+enum Status {
+    /// The answer was written, the map has this size.
+    Ok(usize),
+    /// The file could not be read, it is missing, not allowed or not UTF-8.
+    Unreadable(String),
+    Invalid(String),
+    TimedOut,
+}
+
+struct Report {
+    path: PathBuf,
+    status: Status,
+    elapsed: Duration,
+}
+
+impl Report {
+    fn status(&self) -> &'static str {
+        match self.status {
+            Status::Ok(_) => "ok",
+            Status::Unreadable(_) => "unreadable",
+            Status::Invalid(_) => "invalid",
+            Status::TimedOut => "timed out",
+        }
+    }
+
+    fn size(&self) -> String {
+        match self.status {
+            Status::Ok(size) => format!("{}x{}", size, size),
+            _ => String::new(),
+        }
+    }
+
+    fn reason(&self) -> &str {
+        match &self.status {
+            Status::Unreadable(reason) | Status::Invalid(reason) => reason,
+            _ => "",
+        }
+    }
+}
+
+/// Solves every file of the directories or glob patterns, in parallel,
+/// and writes their answers in the output directory.
+pub fn batch(mut args: impl Iterator<Item = String>) -> anyhow::Result<()> {
+    let mut input = Input::default();
+    let mut options = SearchOptions { timeout: Some(DEFAULT_TIMEOUT), ..SearchOptions::default() };
+    let mut out = None;
+    let mut jobs = thread::available_parallelism().map_or(1, |n| n.get());
+    while let Some(arg) = args.next() {
+        if input.parse_arg(&arg, &mut args)? || parse_search_arg(&mut options, &arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--out" => out = Some(PathBuf::from(args.next().context("missing output directory")?)),
+            "--jobs" => jobs = args.next().context("missing number of jobs")?.parse()?,
+            otherwise => bail!("unknown argument {}", otherwise),
+        }
+    }
+
+    let out = out.context("missing --out directory")?;
+    fs::create_dir_all(&out).with_context(|| format!("creating {}", out.display()))?;
+    let files = find_files(&input.paths)?;
+
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    let mut reports = Vec::with_capacity(files.len());
+    thread::scope(|s| -> anyhow::Result<()> {
+        for _ in 0..jobs.max(1) {
+            let sender = sender.clone();
+            let (next, files, input, options, out) = (&next, &files, &input, &options, &out);
+            s.spawn(move || {
+                while let Some(path) = files.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    if sender.send(solve_file(input, options, out, path)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for report in receiver {
+            let report = report?;
+            eprintln!("{}: {}", report.path.display(), report.status());
+            reports.push(report);
+        }
+        Ok(())
+    })?;
+
+    reports.sort_by(|a, b| a.path.cmp(&b.path));
+    println!("{:<10} {:>7} {:>9}  file", "status", "size", "time");
+    for report in &reports {
+        let elapsed = format!("{:.2}s", report.elapsed.as_secs_f64());
+        print!(
+            "{:<10} {:>7} {:>9}  {}",
+            report.status(),
+            report.size(),
+            elapsed,
+            report.path.display()
+        );
+        match report.reason() {
+            "" => println!(),
+            reason => println!(" ({})", reason),
+        }
+    }
+
+    let count = |status| reports.iter().filter(|r| r.status() == status).count();
+    println!(
+        "{} ok, {} unreadable, {} invalid, {} timed out",
+        count("ok"),
+        count("unreadable"),
+        count("invalid"),
+        count("timed out")
+    );
+
+    Ok(())
+}
+
+/// Lists the `.fillit` files of the directories and the files matching the patterns.
+fn find_files(patterns: &[String]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for pattern in patterns {
+        let pattern = if Path::new(pattern).is_dir() {
+            Path::new(pattern).join("*.fillit").to_string_lossy().into_owned()
+        } else {
+            pattern.clone()
+        };
+        for path in glob::glob(&pattern).with_context(|| format!("invalid pattern {}", pattern))? {
+            files.push(path?);
+        }
+    }
+
+    if files.is_empty() {
+        bail!("no files to solve");
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Solves a file and writes its answer, unreadable files, invalid inputs and timeouts
+/// are reported.
+fn solve_file(
+    input: &Input,
+    options: &SearchOptions,
+    out: &Path,
+    path: &Path,
+) -> anyhow::Result<Report> {
+    let start = Instant::now();
+    let text = fs::read_to_string(path);
+    let status = match text.as_ref().map(|text| input.parse(Some(path), text)) {
+        Err(e) => Status::Unreadable(e.to_string()),
+        Ok(Err(e)) => Status::Invalid(format!("{:#}", e)),
+        Ok(Ok(tetriminos)) => match find_best_fit_with(&tetriminos, options) {
+            Ok((map, _stats)) => {
+                let stem = path.file_stem().context("file without a name")?;
+                let answer = out.join(format!("{}.answer", stem.to_string_lossy()));
+                fs::write(&answer, map.to_string())
+                    .with_context(|| format!("writing {}", answer.display()))?;
+                Status::Ok(map.size())
+            }
+            Err(SearchError::TimedOut) => Status::TimedOut,
+        },
+    };
+
+    Ok(Report { path: path.to_path_buf(), status, elapsed: start.elapsed() })
+}
@@ -0,0 +1,156 @@
+use enum_ordinalize::Ordinalize;
+
+use crate::deadline::Deadline;
+use crate::search_order::constraint_order;
+use crate::{Playground, Position, SearchError, SearchStats, Tetrimino};
+
+/// The state of the search used by `find_bin_packing`, for a given number of maps.
+///
+/// The tetriminos are written one after the other in any map at any position, the maps
+/// are interchangeable so a tetrimino only starts a new map after the ones already used.
+struct BinPacking<'a> {
+    tetriminos: &'a [Tetrimino],
+    // The indices of the tetriminos in the order they are written.
+    order: Vec<usize>,
+    pgs: Vec<Playground>,
+    used: usize,
+    // The first map and position for the next tetrimino of each type,
+    // identical tetriminos are written in this order.
+    farthest: [(usize, Position); Tetrimino::VARIANT_COUNT],
+    wastable: usize,
+    // The number of free tiles isolated by the tetriminos written in all the maps.
+    isolated: usize,
+    placements: Vec<(usize, Position)>,
+    deadline: &'a mut Deadline,
+    stats: &'a mut SearchStats,
+}
+
+/// Returns the map and the position of every tetrimino, in as few maps as possible,
+/// or `None` if one of the tetriminos does not fit in a map of this size. When the deadline
+/// is reached, the first fit packing is returned as not optimal.
+pub fn find_bin_packing(
+    tetriminos: &[Tetrimino],
+    size: usize,
+    deadline: &mut Deadline,
+    stats: &mut SearchStats,
+) -> Option<(Vec<(usize, Position)>, bool)> {
+    let order = constraint_order(tetriminos);
+    let first_fit = first_fit(tetriminos, &order, size)?;
+
+    let used = first_fit.iter().map(|(map, _)| map + 1).max().unwrap_or(0);
+    let area = tetriminos.len() * Tetrimino::TILE_COUNT;
+    let min_maps = area.div_ceil(size * size);
+
+    for count in min_maps..used {
+        let mut search = BinPacking {
+            tetriminos,
+            order: order.clone(),
+            pgs: vec![Playground::from_size(size); count],
+            used: 0,
+            farthest: [(0, Position::default()); Tetrimino::VARIANT_COUNT],
+            wastable: count * size * size - area,
+            isolated: 0,
+            placements: vec![(0, Position::default()); tetriminos.len()],
+            deadline: &mut *deadline,
+            stats: &mut *stats,
+        };
+        // The counts are tried in order, the first packing found uses the fewest maps.
+        match pack(&mut search, 0) {
+            Ok(true) => return Some((search.placements, true)),
+            Ok(false) => (),
+            Err(SearchError::TimedOut) => return Some((first_fit, false)),
+        }
+    }
+
+    Some((first_fit, true))
+}
+
+/// Writes every tetrimino at the first position of the first map where it fits.
+fn first_fit(
+    tetriminos: &[Tetrimino],
+    order: &[usize],
+    size: usize,
+) -> Option<Vec<(usize, Position)>> {
+    let mut pgs: Vec<Playground> = Vec::new();
+    let mut placements = vec![(0, Position::default()); tetriminos.len()];
+    for &i in order {
+        let piece = tetriminos[i].piece();
+        let tsize = tetriminos[i].size();
+        if tsize.row > size || tsize.col > size {
+            return None;
+        }
+
+        let fits = |pg: &Playground| {
+            let positions =
+                (0..size).flat_map(|row| (0..size).map(move |col| Position { col, row }));
+            positions
+                .filter(|p| p.row + tsize.row <= size && p.col + tsize.col <= size)
+                .find(|p| pg.can_write_piece(piece, p))
+        };
+        let (map, pos) = match pgs.iter().enumerate().find_map(|(m, pg)| Some((m, fits(pg)?))) {
+            Some(found) => found,
+            None => {
+                pgs.push(Playground::from_size(size));
+                (pgs.len() - 1, Position::default())
+            }
+        };
+        pgs[map].xor_piece(piece, &pos);
+        placements[i] = (map, pos);
+    }
+    Some(placements)
+}
+
+fn pack(search: &mut BinPacking, k: usize) -> Result<bool, SearchError> {
+    let i = match search.order.get(k) {
+        Some(i) => *i,
+        None => return Ok(true),
+    };
+
+    let tetrimino = search.tetriminos[i];
+    let ttype = tetrimino.ordinal();
+    let piece = tetrimino.piece();
+    let tsize = tetrimino.size();
+    let saved_farthest = search.farthest[ttype];
+    let size = search.pgs[0].size();
+
+    let (first_map, first_pos) = saved_farthest;
+    let last_map = search.used.min(search.pgs.len() - 1);
+    for map in first_map..=last_map {
+        let mut pos = if map == first_map { first_pos } else { Position::default() };
+        while pos.row + tsize.row <= size {
+            while pos.col + tsize.col <= size {
+                if search.pgs[map].can_write_piece(piece, &pos) {
+                    search.pgs[map].xor_piece(piece, &pos);
+                    search.stats.placements += 1;
+                    if search.deadline.reached(search.stats.placements) {
+                        return Err(SearchError::TimedOut);
+                    }
+
+                    let (isolated, used) = (search.isolated, search.used);
+                    search.isolated += search.pgs[map].isolated_tiles_around(piece, &pos);
+                    search.used = used.max(map + 1);
+                    if search.isolated <= search.wastable {
+                        let next =
+                            Position { row: pos.row, col: pos.col + tetrimino.jump_columns() };
+                        search.farthest[ttype] = (map, next);
+                        search.placements[i] = (map, pos);
+                        if pack(search, k + 1)? {
+                            return Ok(true);
+                        }
+                    } else {
+                        search.stats.flood_fill_prunes += 1;
+                    }
+                    search.isolated = isolated;
+                    search.used = used;
+                    search.pgs[map].xor_piece(piece, &pos);
+                }
+                pos.col += 1;
+            }
+            pos.row += 1;
+            pos.col = 0;
+        }
+    }
+
+    search.farthest[ttype] = saved_farthest;
+    Ok(false)
+}
@@ -0,0 +1,11 @@
+use crate::VisualMap;
+
+/// The tetriminos written in as few maps as possible, found by `find_bin_packing`.
+#[derive(Debug, Clone)]
+pub struct Bins {
+    /// The maps, every tetrimino keeps the letter of its index in the input.
+    pub maps: Vec<VisualMap>,
+    /// Whether no packing uses fewer maps, `false` when the timeout was reached first
+    /// and these are the maps of the best packing found so far.
+    pub optimal: bool,
+}
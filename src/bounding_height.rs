@@ -0,0 +1,12 @@
+use crate::{Objective, Position, Tetrimino};
+
+/// Minimises the number of rows used, counted from the top of the map.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BoundingHeight;
+
+impl Objective for BoundingHeight {
+    fn cost(&self, _size: usize, fit: &[(Tetrimino, Option<Position>)]) -> usize {
+        let bottoms = fit.iter().filter_map(|(t, p)| p.map(|p| p.row + t.size().row));
+        bottoms.max().unwrap_or(0)
+    }
+}
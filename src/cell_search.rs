@@ -0,0 +1,244 @@
+use enum_ordinalize::Ordinalize;
+
+use crate::deadline::Deadline;
+use crate::transposition_table::{StateKey, TranspositionTable};
+use crate::{
+    bars_fit, compute_wastable, Playground, Position, SearchError, SearchOptions, SearchStats,
+    Tetrimino, CACHE_MIN_REMAINING, NUMBER_TETRIMINOS,
+};
+
+/// The state of the search used by `SearchOrder::MostConstrainedCell`.
+///
+/// Instead of writing the tetriminos one after the other, this search picks a free
+/// tile and tries every tetrimino that can cover it, or leaves it empty.
+struct CellSearch<'a> {
+    pg: Playground,
+    wastable: usize,
+    wasted: usize,
+    // The tetriminos that are not written yet, by type, as bitsets of their indices.
+    remaining: [u32; Tetrimino::VARIANT_COUNT],
+    solution: [Position; NUMBER_TETRIMINOS],
+    // The states from which no solution can be found.
+    table: Option<TranspositionTable>,
+    deadline: &'a mut Deadline,
+    stats: &'a mut SearchStats,
+}
+
+impl<'a> CellSearch<'a> {
+    fn new(
+        tetriminos: &[Tetrimino],
+        options: &SearchOptions,
+        size: usize,
+        deadline: &'a mut Deadline,
+        stats: &'a mut SearchStats,
+    ) -> CellSearch<'a> {
+        let mut remaining = [0; Tetrimino::VARIANT_COUNT];
+        for (i, t) in tetriminos.iter().enumerate() {
+            remaining[t.ordinal()] |= 1 << i;
+        }
+
+        CellSearch {
+            pg: Playground::from_size(size),
+            wastable: compute_wastable(size, tetriminos.len()),
+            wasted: 0,
+            remaining,
+            solution: [Position::default(); NUMBER_TETRIMINOS],
+            table: options.cache_memory.map(TranspositionTable::with_memory),
+            deadline,
+            stats,
+        }
+    }
+}
+
+pub fn find_best_fit(
+    tetriminos: &[Tetrimino],
+    options: &SearchOptions,
+    min_size: usize,
+    deadline: &mut Deadline,
+    stats: &mut SearchStats,
+) -> Result<(Vec<Position>, usize), SearchError> {
+    let types_mask = tetriminos.iter().fold(0, |mask, t| mask | 1 << t.ordinal());
+    let fitting_size = |size| (size..).find(|&s| bars_fit(types_mask, tetriminos.len(), s));
+    let size = Playground::from_number_tetriminos(tetriminos.len()).size().max(min_size);
+    let size = fitting_size(size).expect("a size fits the bars");
+    let mut search = CellSearch::new(tetriminos, options, size, deadline, stats);
+
+    while !fill(&mut search) {
+        if search.deadline.reached(search.stats.placements) {
+            return Err(SearchError::TimedOut);
+        }
+        let size = fitting_size(search.pg.size() + 1).expect("a size fits the bars");
+        search.pg = Playground::from_size(size);
+        search.wastable = compute_wastable(search.pg.size(), tetriminos.len());
+        if let Some(table) = &mut search.table {
+            table.clear();
+        }
+    }
+
+    Ok((search.solution[..tetriminos.len()].to_vec(), search.pg.size()))
+}
+
+/// Fills a map of exactly this size with the tetriminos, if they fit in it.
+pub fn find_fit_at_size(
+    tetriminos: &[Tetrimino],
+    options: &SearchOptions,
+    size: usize,
+    deadline: &mut Deadline,
+    stats: &mut SearchStats,
+) -> Result<Option<Vec<Position>>, SearchError> {
+    let types_mask = tetriminos.iter().fold(0, |mask, t| mask | 1 << t.ordinal());
+    if size * size < tetriminos.len() * Tetrimino::TILE_COUNT
+        || !bars_fit(types_mask, tetriminos.len(), size)
+    {
+        return Ok(None);
+    }
+
+    let mut search = CellSearch::new(tetriminos, options, size, deadline, stats);
+    if fill(&mut search) {
+        Ok(Some(search.solution[..tetriminos.len()].to_vec()))
+    } else if search.deadline.reached(search.stats.placements) {
+        Err(SearchError::TimedOut)
+    } else {
+        Ok(None)
+    }
+}
+
+fn fill(search: &mut CellSearch) -> bool {
+    let remaining_count: u32 = search.remaining.iter().map(|r| r.count_ones()).sum();
+    if remaining_count == 0 {
+        return true;
+    }
+    if search.deadline.reached(search.stats.placements) {
+        return false;
+    }
+
+    // We already explored this exact state and did not find any solution.
+    let key = match &search.table {
+        Some(table) if remaining_count as usize >= CACHE_MIN_REMAINING => {
+            let key = StateKey::with_remaining(search.pg.lines(), &search.remaining);
+            if table.contains(&key) {
+                search.stats.cache_hits += 1;
+                return false;
+            }
+            search.stats.cache_misses += 1;
+            Some(key)
+        }
+        _ => None,
+    };
+
+    let found = fill_most_constrained(search);
+    let dead = !found && !search.deadline.reached(search.stats.placements);
+    if let Some((table, key)) = search.table.as_mut().zip(key).filter(|_| dead) {
+        table.insert(key);
+    }
+    found
+}
+
+fn fill_most_constrained(search: &mut CellSearch) -> bool {
+    let size = search.pg.size();
+
+    // Where each type can be written and whether each tile
+    // can be covered by a single or by multiple tetriminos.
+    let mut valid = [[0u16; 16]; Tetrimino::VARIANT_COUNT];
+    let mut once = [0u16; 16];
+    let mut more = [0u16; 16];
+    for (ttype, v) in valid.iter_mut().enumerate().filter(|(t, _)| search.remaining[*t] != 0) {
+        let piece = Tetrimino::VARIANTS[ttype].piece();
+        *v = search.pg.valid_positions(piece, &Position::default());
+        for tile in piece.tiles() {
+            for row in (0..size).filter(|r| v[*r] != 0) {
+                let covered = v[row] >> tile.col;
+                more[row + tile.row] |= once[row + tile.row] & covered;
+                once[row + tile.row] |= covered;
+            }
+        }
+    }
+
+    // The free tiles that no tetrimino can cover must be left empty.
+    let free = search.pg.lines().iter().map(|line| !line);
+    let uncoverable: Vec<_> = free.clone().zip(&once).map(|(f, o)| f & !o).collect();
+    let count: usize = uncoverable.iter().map(|u| u.count_ones() as usize).sum();
+    if search.wasted + count > search.wastable {
+        search.stats.unreachable_prunes += 1;
+        return false;
+    }
+
+    if count != 0 {
+        let tiles = positions(&uncoverable);
+        tiles.iter().for_each(|pos| search.pg.xor_tile(pos));
+        search.wasted += count;
+        let found = fill(search);
+        search.wasted -= count;
+        tiles.iter().for_each(|pos| search.pg.xor_tile(pos));
+        return found;
+    }
+
+    // We prefer a tile that only one tetrimino can cover, or the first free one.
+    let single: Vec<_> = once.iter().zip(&more).map(|(o, m)| o & !m).collect();
+    let target = match positions(&single).first() {
+        Some(pos) => *pos,
+        None => {
+            let free: Vec<_> = free.collect();
+            positions(&free)[0]
+        }
+    };
+
+    for (ttype, v) in valid.iter().enumerate() {
+        if search.remaining[ttype] == 0 {
+            continue;
+        }
+
+        // Identical tetriminos are interchangeable, we only try the first one of each type.
+        let index = search.remaining[ttype].trailing_zeros() as usize;
+        let piece = Tetrimino::VARIANTS[ttype].piece();
+
+        for tile in piece.tiles() {
+            let pos = match (target.row.checked_sub(tile.row), target.col.checked_sub(tile.col)) {
+                (Some(row), Some(col)) => Position { row, col },
+                _ => continue,
+            };
+            if v[pos.row] & (1 << (15 - pos.col)) == 0 {
+                continue;
+            }
+
+            search.pg.xor_piece(piece, &pos);
+            search.remaining[ttype] ^= 1 << index;
+            search.solution[index] = pos;
+            search.stats.placements += 1;
+
+            let found = fill(search);
+
+            search.remaining[ttype] ^= 1 << index;
+            search.pg.xor_piece(piece, &pos);
+            if found {
+                return true;
+            }
+        }
+    }
+
+    // None of the tetriminos worked, the tile could be one of the wasted ones.
+    if search.wasted < search.wastable {
+        search.pg.xor_tile(&target);
+        search.wasted += 1;
+        let found = fill(search);
+        search.wasted -= 1;
+        search.pg.xor_tile(&target);
+        return found;
+    }
+
+    false
+}
+
+/// Returns the positions of the bits set in these lines, row by row.
+fn positions(lines: &[u16]) -> Vec<Position> {
+    let mut positions = Vec::new();
+    for (row, line) in lines.iter().enumerate() {
+        let mut line = *line;
+        while line != 0 {
+            let col = line.leading_zeros() as usize;
+            line &= !(1 << (15 - col));
+            positions.push(Position { col, row });
+        }
+    }
+    positions
+}
@@ -0,0 +1,44 @@
+use anyhow::Context;
+
+use crate::{ensure_count, ParseError, Tetrimino};
+
+/// Parses one tetrimino per line, either its name as returned by `Tetrimino::name`
+/// or the hexadecimal mask of its 4x4 map as returned by `Tetrimino::mask`.
+///
+/// ```text
+/// L90
+/// T
+/// 0xcc00
+/// ```
+///
+/// Blank lines and spaces around the tetriminos are ignored.
+pub fn parse_compact(text: &str) -> anyhow::Result<Vec<Tetrimino>> {
+    let tetriminos = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .enumerate()
+        .map(|(i, line)| parse_piece(line).with_context(|| format!("number {}", i)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    ensure_count(&tetriminos)?;
+    Ok(tetriminos)
+}
+
+/// Parses the name or the hexadecimal mask of a tetrimino.
+pub(crate) fn parse_piece(text: &str) -> anyhow::Result<Tetrimino> {
+    if let Some(tetrimino) = Tetrimino::from_name(text) {
+        return Ok(tetrimino);
+    }
+
+    let digits = text.strip_prefix("0x").unwrap_or(text);
+    match u16::from_str_radix(digits, 16) {
+        Ok(mask) => Tetrimino::from_mask(mask),
+        Err(_) => Err(ParseError::UnknownPiece.into()),
+    }
+}
+
+/// Writes the name of every tetrimino, one per line.
+pub fn to_compact(tetriminos: &[Tetrimino]) -> String {
+    tetriminos.iter().flat_map(|t| [t.name(), "\n"]).collect()
+}
@@ -0,0 +1,25 @@
+use std::time::{Duration, Instant};
+
+/// The clock is only read once every this number of placements.
+const CHECK_INTERVAL: u64 = 1 << 14;
+
+/// The instant after which a search gives up.
+pub struct Deadline {
+    instant: Option<Instant>,
+    reached: bool,
+}
+
+impl Deadline {
+    pub fn after(timeout: Option<Duration>) -> Deadline {
+        Deadline { instant: timeout.map(|t| Instant::now() + t), reached: false }
+    }
+
+    /// Returns `true` once the deadline is reached, given the number of placements done so far.
+    #[inline]
+    pub fn reached(&mut self, placements: u64) -> bool {
+        if !self.reached && placements.is_multiple_of(CHECK_INTERVAL) {
+            self.reached = self.instant.is_some_and(|i| Instant::now() >= i);
+        }
+        self.reached
+    }
+}
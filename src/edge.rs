@@ -0,0 +1,26 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+
+/// A side of the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl FromStr for Edge {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Edge> {
+        match s {
+            "top" => Ok(Edge::Top),
+            "bottom" => Ok(Edge::Bottom),
+            "left" => Ok(Edge::Left),
+            "right" => Ok(Edge::Right),
+            otherwise => bail!("unknown edge {}", otherwise),
+        }
+    }
+}
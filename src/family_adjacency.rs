@@ -0,0 +1,27 @@
+use crate::objective::tiles;
+use crate::{Objective, Position, Tetrimino};
+
+/// Minimises the number of pairs of tetriminos of the same family, like an `L` and an `L90`,
+/// that do not touch each other by a side.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FamilyAdjacency;
+
+impl Objective for FamilyAdjacency {
+    fn cost(&self, _size: usize, fit: &[(Tetrimino, Option<Position>)]) -> usize {
+        let written: Vec<_> = fit.iter().filter_map(|(t, p)| Some((*t, (*p)?))).collect();
+        let mut apart = 0;
+        for (i, (a, pa)) in written.iter().enumerate() {
+            for (b, pb) in &written[i + 1..] {
+                if a.family() == b.family() && !touching(*a, *pa, *b, *pb) {
+                    apart += 1;
+                }
+            }
+        }
+        apart
+    }
+}
+
+fn touching(a: Tetrimino, pa: Position, b: Tetrimino, pb: Position) -> bool {
+    tiles(a, pa)
+        .any(|ta| tiles(b, pb).any(|tb| ta.row.abs_diff(tb.row) + ta.col.abs_diff(tb.col) == 1))
+}
@@ -0,0 +1,204 @@
+//! The C interface, the header is generated in `include/fillit.h` when building with the
+//! `ffi` feature. Every map returned by `fillit_parse` and `fillit_solve` must be given
+//! back to `fillit_free`.
+
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::ptr;
+use std::time::Duration;
+
+use enum_ordinalize::Ordinalize;
+
+use crate::{
+    find_best_fit_with, parse_tetriminos_with, ParseError, ParseMode, ParseOptions, SearchError,
+    SearchOptions, Tetrimino, VisualMap, NUMBER_TETRIMINOS,
+};
+
+/// The result of every function, the parse errors have the codes from 1 to 99.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillitStatus {
+    FillitOk = 0,
+    FillitInvalidCharacter = 1,
+    FillitLineTooLong = 2,
+    FillitLineTooShort = 3,
+    FillitTooManyLines = 4,
+    FillitTooFewLines = 5,
+    FillitInvalidTetrimino = 6,
+    FillitEmptyTetrimino = 7,
+    FillitUnknownPiece = 8,
+    FillitInvalidJson = 9,
+    FillitMissingNewline = 10,
+    FillitNoTetriminos = 11,
+    FillitTooManyTetriminos = 12,
+    /// One of the pointers given is null.
+    FillitNullPointer = 100,
+    /// The text is not valid UTF-8.
+    FillitInvalidUtf8 = 101,
+    /// A tetrimino variant is not one of the known ones.
+    FillitInvalidVariant = 102,
+    /// The search took longer than the timeout.
+    FillitTimedOut = 103,
+}
+
+impl From<ParseError> for FillitStatus {
+    fn from(error: ParseError) -> FillitStatus {
+        match error {
+            ParseError::InvalidCharacter(_) => FillitStatus::FillitInvalidCharacter,
+            ParseError::LineTooLong => FillitStatus::FillitLineTooLong,
+            ParseError::LineTooShort => FillitStatus::FillitLineTooShort,
+            ParseError::TooManyLines => FillitStatus::FillitTooManyLines,
+            ParseError::TooFewLines => FillitStatus::FillitTooFewLines,
+            ParseError::InvalidTetrimino => FillitStatus::FillitInvalidTetrimino,
+            ParseError::EmptyTetrimino => FillitStatus::FillitEmptyTetrimino,
+            ParseError::UnknownPiece => FillitStatus::FillitUnknownPiece,
+            ParseError::InvalidJson => FillitStatus::FillitInvalidJson,
+            ParseError::MissingNewline => FillitStatus::FillitMissingNewline,
+            ParseError::NoTetriminos => FillitStatus::FillitNoTetriminos,
+            ParseError::TooManyTetriminos => FillitStatus::FillitTooManyTetriminos,
+        }
+    }
+}
+
+/// A tetrimino, its letter in the answers and where it is written.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FillitPlacement {
+    pub letter: c_char,
+    pub row: u8,
+    pub col: u8,
+    /// The index of the shape in the `Tetrimino` enum, named in order I, I90, O,
+    /// L, L90, L180, L270, J, J90, J180, J270, S, S90, Z, Z90, T, T90, T180 and T270.
+    pub variant: u8,
+}
+
+/// The tetriminos in the input order, their positions are only meaningful once solved.
+#[repr(C)]
+#[derive(Debug)]
+pub struct FillitMap {
+    /// The side of the map, 0 when it is not solved.
+    pub size: usize,
+    pub count: usize,
+    pub placements: *mut FillitPlacement,
+}
+
+impl FillitMap {
+    fn from_visual_map(map: &VisualMap) -> *mut FillitMap {
+        let placements = map.tetriminos().iter().zip(b'A'..).map(|((t, p), letter)| {
+            let variant = t.ordinal() as u8;
+            FillitPlacement {
+                letter: letter as c_char,
+                row: p.row as u8,
+                col: p.col as u8,
+                variant,
+            }
+        });
+        let placements: Box<[_]> = placements.collect();
+        let count = placements.len();
+        let placements = Box::into_raw(placements) as *mut FillitPlacement;
+        Box::into_raw(Box::new(FillitMap { size: map.size(), count, placements }))
+    }
+
+    /// The tetriminos of a map returned by this interface or built by hand.
+    ///
+    /// # Safety
+    ///
+    /// `placements` must point to `count` placements when it is not null.
+    unsafe fn tetriminos(&self) -> Result<Vec<Tetrimino>, FillitStatus> {
+        if self.count == 0 {
+            return Ok(Vec::new());
+        }
+        if self.count > NUMBER_TETRIMINOS {
+            return Err(FillitStatus::FillitTooManyTetriminos);
+        }
+        if self.placements.is_null() {
+            return Err(FillitStatus::FillitNullPointer);
+        }
+        let placements = std::slice::from_raw_parts(self.placements, self.count);
+        let variants = placements.iter().map(|p| Tetrimino::VARIANTS.get(p.variant as usize));
+        variants.map(|t| t.copied().ok_or(FillitStatus::FillitInvalidVariant)).collect()
+    }
+}
+
+/// Parses the 42 subject format, strictly or leniently, and writes
+/// the unsolved tetriminos in `map` when the status is `FillitOk`.
+///
+/// # Safety
+///
+/// `text` must be a NUL-terminated string and `map` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn fillit_parse(
+    text: *const c_char,
+    strict: bool,
+    map: *mut *mut FillitMap,
+) -> FillitStatus {
+    if text.is_null() || map.is_null() {
+        return FillitStatus::FillitNullPointer;
+    }
+    let text = match CStr::from_ptr(text).to_str() {
+        Ok(text) => text,
+        Err(_) => return FillitStatus::FillitInvalidUtf8,
+    };
+
+    let mode = if strict { ParseMode::Strict } else { ParseMode::Lenient };
+    let options = ParseOptions { mode, ..ParseOptions::default() };
+    match parse_tetriminos_with(text, &options) {
+        Ok(tetriminos) => {
+            let unsolved = tetriminos.into_iter().map(|t| (t, Default::default())).collect();
+            *map = FillitMap::from_visual_map(&VisualMap::new(unsolved, 0));
+            FillitStatus::FillitOk
+        }
+        // The parsing errors are all `ParseError`s.
+        Err(e) => e
+            .downcast_ref::<ParseError>()
+            .map_or(FillitStatus::FillitInvalidTetrimino, |e| FillitStatus::from(*e)),
+    }
+}
+
+/// Finds the lexicographic best fit of the tetriminos and writes it in `solution`
+/// when the status is `FillitOk`, the search gives up after `timeout_ms` unless it is 0.
+///
+/// # Safety
+///
+/// `tetriminos` must come from `fillit_parse` or `fillit_solve`, or be built by hand with
+/// `placements` pointing to `count` placements, and `solution` must be a valid pointer.
+/// Only the variants are read, a map of more than 26 tetriminos is rejected.
+#[no_mangle]
+pub unsafe extern "C" fn fillit_solve(
+    tetriminos: *const FillitMap,
+    timeout_ms: u32,
+    solution: *mut *mut FillitMap,
+) -> FillitStatus {
+    if tetriminos.is_null() || solution.is_null() {
+        return FillitStatus::FillitNullPointer;
+    }
+    let tetriminos = match (*tetriminos).tetriminos() {
+        Ok(tetriminos) => tetriminos,
+        Err(status) => return status,
+    };
+
+    let timeout = Some(Duration::from_millis(timeout_ms.into())).filter(|t| !t.is_zero());
+    let options = SearchOptions { timeout, ..SearchOptions::default() };
+    match find_best_fit_with(&tetriminos, &options) {
+        Ok((map, _stats)) => {
+            *solution = FillitMap::from_visual_map(&map);
+            FillitStatus::FillitOk
+        }
+        Err(SearchError::TimedOut) => FillitStatus::FillitTimedOut,
+    }
+}
+
+/// Frees a map returned by `fillit_parse` or `fillit_solve`, null is ignored.
+///
+/// # Safety
+///
+/// `map` must not be used after this call.
+#[no_mangle]
+pub unsafe extern "C" fn fillit_free(map: *mut FillitMap) {
+    if map.is_null() {
+        return;
+    }
+    let map = Box::from_raw(map);
+    let placements = ptr::slice_from_raw_parts_mut(map.placements, map.count);
+    drop(Box::from_raw(placements));
+}
@@ -0,0 +1,101 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+
+use crate::{
+    find_best_fit_with, parse_answer, parse_compact, parse_json, parse_tetriminos_with, to_compact,
+    to_fillit, to_json, ParseOptions, SearchError, SearchOptions, Tetrimino,
+};
+
+/// The formats the tetriminos can be read from and written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// The blocks of 4x4 tiles of the 42 subject, read by `parse_tetriminos`.
+    Fillit,
+    /// One tetrimino name or mask per line, read by `parse_compact`.
+    Compact,
+    /// An array of tetrimino names, masks or lines, read by `parse_json`.
+    Json,
+    /// A solved map, read by `parse_answer`, the tetriminos are solved again when written.
+    Answer,
+}
+
+impl Format {
+    /// Finds the format associated with this file extension.
+    pub fn from_extension(extension: &str) -> Option<Format> {
+        match extension {
+            "fillit" => Some(Format::Fillit),
+            "compact" => Some(Format::Compact),
+            "json" => Some(Format::Json),
+            "answer" => Some(Format::Answer),
+            _otherwise => None,
+        }
+    }
+
+    /// Guesses the format of this input, made with the characters of these options.
+    ///
+    /// An answer is a square of letters, a compact line never contains a tile character
+    /// and anything else is read as the 42 subject format to report its errors.
+    pub fn detect(text: &str, options: &ParseOptions) -> Format {
+        let is_tile = |c: char| c == options.empty || c == options.full;
+        let is_padding = |c: char| c.is_whitespace() && !is_tile(c);
+        let lines: Vec<_> =
+            text.lines().map(|l| l.trim_matches(is_padding)).filter(|l| !l.is_empty()).collect();
+
+        if text.trim_start().starts_with('[') {
+            Format::Json
+        } else if lines.iter().all(|l| l.chars().all(is_tile)) {
+            Format::Fillit
+        } else if lines.len() > 1
+            && lines[0].len() > 1
+            && lines.iter().all(|l| l.len() == lines[0].len())
+            && lines
+                .iter()
+                .flat_map(|l| l.chars())
+                .all(|c| c == options.empty || c.is_ascii_uppercase())
+        {
+            Format::Answer
+        } else if lines.iter().all(|l| !l.contains(is_tile)) {
+            Format::Compact
+        } else {
+            Format::Fillit
+        }
+    }
+
+    pub fn parse(self, text: &str, options: &ParseOptions) -> anyhow::Result<Vec<Tetrimino>> {
+        match self {
+            Format::Fillit => parse_tetriminos_with(text, options),
+            Format::Compact => parse_compact(text),
+            Format::Json => parse_json(text),
+            Format::Answer => parse_answer(text, options.empty),
+        }
+    }
+
+    /// Writes the tetriminos, an answer is solved with these options and fails on timeout.
+    pub fn write(
+        self,
+        tetriminos: &[Tetrimino],
+        options: &SearchOptions,
+    ) -> Result<String, SearchError> {
+        Ok(match self {
+            Format::Fillit => to_fillit(tetriminos),
+            Format::Compact => to_compact(tetriminos),
+            Format::Json => to_json(tetriminos) + "\n",
+            Format::Answer => find_best_fit_with(tetriminos, options)?.0.to_string(),
+        })
+    }
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Format> {
+        match s {
+            "fillit" => Ok(Format::Fillit),
+            "compact" => Ok(Format::Compact),
+            "json" => Ok(Format::Json),
+            "answer" => Ok(Format::Answer),
+            otherwise => bail!("unknown format {}", otherwise),
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::convert::TryFrom;
+
+use anyhow::Context;
+use serde_json::Value;
+
+use crate::{compact, ensure_count, ParseError, Tetrimino};
+
+/// Parses a JSON array of tetriminos, each one is either a string with its
+/// name or hexadecimal mask like in the compact format, the mask as a number,
+/// or an array of the four lines of its 4x4 map made of `.` and `#`.
+///
+/// ```text
+/// ["L90", 52224, ["..#.", "###.", "....", "...."]]
+/// ```
+pub fn parse_json(text: &str) -> anyhow::Result<Vec<Tetrimino>> {
+    let pieces = match serde_json::from_str(text) {
+        Ok(Value::Array(pieces)) => pieces,
+        _ => return Err(ParseError::InvalidJson.into()),
+    };
+
+    let tetriminos = pieces
+        .iter()
+        .enumerate()
+        .map(|(i, piece)| parse_piece(piece).with_context(|| format!("number {}", i)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    ensure_count(&tetriminos)?;
+    Ok(tetriminos)
+}
+
+fn parse_piece(piece: &Value) -> anyhow::Result<Tetrimino> {
+    match piece {
+        Value::String(text) => compact::parse_piece(text),
+        Value::Number(mask) => match mask.as_u64().and_then(|m| u16::try_from(m).ok()) {
+            Some(mask) => Tetrimino::from_mask(mask),
+            None => Err(ParseError::UnknownPiece.into()),
+        },
+        Value::Array(lines) => {
+            let lines: Option<Vec<_>> = lines.iter().map(Value::as_str).collect();
+            let lines = lines.ok_or(ParseError::InvalidJson)?;
+            Tetrimino::from_strict_text(&lines.join("\n"))
+        }
+        _ => Err(ParseError::InvalidJson.into()),
+    }
+}
+
+/// Writes a JSON array of the tetriminos names.
+pub fn to_json(tetriminos: &[Tetrimino]) -> String {
+    let names: Vec<_> = tetriminos.iter().map(|t| Value::from(t.name())).collect();
+    Value::Array(names).to_string()
+}
@@ -1,41 +1,158 @@
+use std::ops::RangeInclusive;
+
 use anyhow::{ensure, Context};
 use enum_ordinalize::Ordinalize;
 
+mod answer;
+mod bin_packing;
+mod bins;
 mod boolean_maps;
+mod bounding_height;
+mod cell_search;
+mod compact;
+mod deadline;
+mod edge;
+mod family_adjacency;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod format;
+mod json;
+mod max_packing;
+mod objective;
+mod objective_fit;
+mod objective_search;
+mod packing;
+mod parse_error;
+mod parse_mode;
+mod parse_options;
 mod piece;
+mod pin;
+mod pinned_search;
 mod playground;
 mod position;
+mod priority_pieces;
+#[cfg(feature = "python")]
+mod python;
+mod search_error;
+mod search_options;
+mod search_order;
+mod search_stats;
+mod solver;
+mod step_control;
+mod step_event;
 mod tetrimino;
 mod tetriminos;
+mod top_left_holes;
+mod trace_format;
+mod trace_reader;
+mod trace_writer;
+mod transposition_table;
 mod visual_map;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod weighted;
 
+pub use self::answer::parse_answer;
+pub use self::bins::Bins;
+pub use self::bounding_height::BoundingHeight;
+pub use self::compact::{parse_compact, to_compact};
+pub use self::edge::Edge;
+pub use self::family_adjacency::FamilyAdjacency;
+pub use self::format::Format;
+pub use self::json::{parse_json, to_json};
+pub use self::objective::Objective;
+pub use self::objective_fit::ObjectiveFit;
+pub use self::packing::Packing;
+pub use self::parse_error::ParseError;
+pub use self::parse_mode::ParseMode;
+pub use self::parse_options::ParseOptions;
 pub use self::piece::Piece;
-pub use self::playground::Playground;
+pub use self::pin::Pin;
+pub use self::playground::{Playground, MAX_SIZE};
 pub use self::position::Position;
+pub use self::priority_pieces::PriorityPieces;
+pub use self::search_error::SearchError;
+pub use self::search_options::SearchOptions;
+pub use self::search_order::SearchOrder;
+pub use self::search_stats::SearchStats;
+pub use self::solver::Solver;
+pub use self::step_control::StepControl;
+pub use self::step_event::StepEvent;
 pub use self::tetrimino::Tetrimino;
 pub use self::tetriminos::Tetriminos;
+pub use self::top_left_holes::TopLeftHoles;
+pub use self::trace_format::TraceFormat;
+pub use self::trace_reader::TraceReader;
+pub use self::trace_writer::TraceWriter;
+use self::transposition_table::{StateKey, TranspositionTable};
 pub use self::visual_map::VisualMap;
+pub use self::weighted::Weighted;
 
 const NUMBER_TETRIMINOS: usize = 26;
 
+/// The dead states are only cached when there are enough tetriminos left to place,
+/// the deepest subtrees are cheaper to explore than to look up.
+const CACHE_MIN_REMAINING: usize = 4;
+
+/// The wasted tiles are only checked for the first tetriminos.
+const WASTED_CHECK_DEPTH: usize = 9;
+
+use self::deadline::Deadline;
 use BacktrackResult::*;
 
 pub fn parse_tetriminos(text: &str) -> anyhow::Result<Vec<Tetrimino>> {
-    let tetriminos: anyhow::Result<Vec<_>> = text
-        .split("\n\n")
+    parse_tetriminos_with(text, &ParseOptions::default())
+}
+
+/// Parses the tetriminos, the errors can be downcasted to a `ParseError`.
+pub fn parse_tetriminos_with(text: &str, options: &ParseOptions) -> anyhow::Result<Vec<Tetrimino>> {
+    let tetriminos = match options.mode {
+        ParseMode::Strict => parse_strict(text)?,
+        ParseMode::Lenient => parse_lenient(text, options.empty, options.full)?,
+    };
+
+    ensure_count(&tetriminos)?;
+    Ok(tetriminos)
+}
+
+/// Writes the tetriminos in the format read by `parse_tetriminos`.
+pub fn to_fillit(tetriminos: &[Tetrimino]) -> String {
+    let blocks: Vec<_> = tetriminos.iter().map(|t| t.to_text('.', '#')).collect();
+    blocks.join("\n")
+}
+
+fn ensure_count(tetriminos: &[Tetrimino]) -> anyhow::Result<()> {
+    ensure!(!tetriminos.is_empty(), ParseError::NoTetriminos);
+    ensure!(tetriminos.len() <= NUMBER_TETRIMINOS, ParseError::TooManyTetriminos);
+    Ok(())
+}
+
+fn parse_strict(text: &str) -> anyhow::Result<Vec<Tetrimino>> {
+    if text.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let text = text.strip_suffix('\n').ok_or(ParseError::MissingNewline)?;
+    text.split("\n\n")
         .enumerate()
         .map(|(i, block)| {
-            Tetrimino::from_text(block, '.', '#').with_context(|| format!("number {}", i))
+            Tetrimino::from_strict_text(block).with_context(|| format!("number {}", i))
         })
-        .collect();
+        .collect()
+}
 
-    let tetriminos = tetriminos?;
-    ensure!(
-        tetriminos.len() <= NUMBER_TETRIMINOS,
-        "too much tetriminos (max is {})",
-        NUMBER_TETRIMINOS
-    );
-    Ok(tetriminos)
+fn parse_lenient(text: &str, empty: char, full: char) -> anyhow::Result<Vec<Tetrimino>> {
+    let is_padding = |c: char| c.is_whitespace() && c != empty && c != full;
+    let lines: Vec<_> = text.lines().collect();
+    lines
+        .split(|line| line.trim_matches(is_padding).is_empty())
+        .filter(|block| !block.is_empty())
+        .enumerate()
+        .map(|(i, block)| {
+            Tetrimino::from_text(&block.join("\n"), empty, full)
+                .with_context(|| format!("number {}", i))
+        })
+        .collect()
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -43,60 +160,256 @@ enum BacktrackResult {
     SolutionFound,
     NeedNewMap,
     Continue,
+    TimedOut,
 }
 
-fn backtrack(
-    tetriminos: &Tetriminos,
-    i: usize,
-    pg: &mut Playground,
+/// Called on every step of the backtracking with the farthest position of each tetrimino type.
+pub type StepHook<'a> = dyn FnMut(StepEvent, &[Position]) -> StepControl + 'a;
+
+/// The state shared by every level of the backtracking.
+struct Search<'a, 'h> {
+    tetriminos: &'a Tetriminos,
+    options: &'a SearchOptions,
+    pg: Playground,
     wastable: usize,
-    solution: &mut [Position],
-    farthest: &mut [Position],
-) -> BacktrackResult {
+    // The number of free tiles isolated by the pieces written in the playground.
+    isolated: usize,
+    // The farthest position for a given piece type.
+    farthest: [Position; Tetrimino::VARIANT_COUNT],
+    // The states from which no solution can be found.
+    table: Option<TranspositionTable>,
+    deadline: &'a mut Deadline,
+    stats: &'a mut SearchStats,
+    hook: Option<&'a mut StepHook<'h>>,
+    // The positions of the tetriminos written so far.
+    positions: [Position; NUMBER_TETRIMINOS],
+    // The solutions found so far when enumerating them, along with the number wanted.
+    solutions: Option<(&'a mut Vec<Vec<Position>>, usize)>,
+    // The number of tiles covered by the tetriminos written before searching.
+    pinned: usize,
+}
+
+impl<'a> Search<'a, '_> {
+    /// Prepares the search of the tetriminos in the free tiles of this playground.
+    fn new(
+        tetriminos: &'a Tetriminos,
+        options: &'a SearchOptions,
+        pg: Playground,
+        deadline: &'a mut Deadline,
+        stats: &'a mut SearchStats,
+    ) -> Self {
+        let free = pg.lines().iter().map(|l| l.count_zeros() as usize).sum::<usize>();
+        let pinned = pg.size() * pg.size() - free;
+        Search {
+            tetriminos,
+            options,
+            wastable: free - tetriminos.count * Tetrimino::TILE_COUNT,
+            isolated: 0,
+            pg,
+            farthest: [Position::default(); Tetrimino::VARIANT_COUNT],
+            table: options.cache_memory.map(TranspositionTable::with_memory),
+            deadline,
+            stats,
+            hook: None,
+            positions: [Position::default(); NUMBER_TETRIMINOS],
+            solutions: None,
+            pinned,
+        }
+    }
+
+    /// Writes the tetriminos in order and returns their positions, if they fit.
+    fn run(&mut self) -> Result<Option<Vec<Position>>, SearchError> {
+        let count = self.tetriminos.count;
+        if count == 0 {
+            return Ok(Some(Vec::new()));
+        }
+
+        let mut solution = [Position::default(); NUMBER_TETRIMINOS];
+        match backtrack(self, 0, &mut solution[..count]) {
+            SolutionFound => Ok(Some(solution[..count].to_vec())),
+            TimedOut => Err(SearchError::TimedOut),
+            NeedNewMap | Continue => Ok(None),
+        }
+    }
+
+    /// Tells the hook about this step, returns `true` if it wants the next size
+    /// and there is a bigger one.
+    fn step(&mut self, event: StepEvent) -> bool {
+        match &mut self.hook {
+            Some(hook) => {
+                hook(event, &self.farthest) == StepControl::NextSize && self.pg.size() < MAX_SIZE
+            }
+            None => false,
+        }
+    }
+
+    /// Keeps the solution just found when enumerating them,
+    /// returns `true` if the search must look for more.
+    fn record_solution(&mut self) -> bool {
+        match &mut self.solutions {
+            Some((solutions, limit)) => {
+                solutions.push(self.positions[..self.tetriminos.count].to_vec());
+                solutions.len() < *limit
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the first position where one of the tetriminos from this index can be written.
+    ///
+    /// Identical tetriminos are interchangeable, we only explore the permutation where they
+    /// are written in order, each one after the farthest position of the previous ones.
+    fn first_available_position(&self, i: usize) -> Position {
+        let mut types = self.tetriminos.remaining_types[i];
+        let mut first = None;
+        while types != 0 {
+            let far = self.farthest[types.trailing_zeros() as usize];
+            types &= types - 1;
+            if first.is_none_or(|f: Position| (far.row, far.col) < (f.row, f.col)) {
+                first = Some(far);
+            }
+        }
+        first.unwrap_or_default()
+    }
+
+    /// Returns `true` if the free tiles that none of the tetriminos from this index
+    /// can cover exceed the wastable ones.
+    fn unreachable(&self, i: usize) -> bool {
+        // No tetrimino left can cover the free tiles before this position.
+        let first = self.first_available_position(i);
+        self.pg.free_tiles_before(&first) > self.wastable
+            || self.unreachable_tiles(i) > self.wastable
+    }
+
+    /// Returns the number of free tiles that none of the tetriminos from this index can cover.
+    fn unreachable_tiles(&self, i: usize) -> usize {
+        let mut coverable = [0u16; 16];
+        let mut types = self.tetriminos.remaining_types[i];
+        while types != 0 {
+            let ttype = types.trailing_zeros() as usize;
+            types &= types - 1;
+            let piece = Tetrimino::VARIANTS[ttype].piece();
+            let tiles = self.pg.coverable_tiles(piece, &self.farthest[ttype]);
+            coverable.iter_mut().zip(&tiles).for_each(|(c, t)| *c |= t);
+        }
+
+        self.pg.lines().iter().zip(&coverable).map(|(l, c)| (!l & !c).count_ones() as usize).sum()
+    }
+
+    fn state_key(&self, i: usize) -> StateKey {
+        // The wasted tiles check looks at every tetrimino type, the subtree
+        // therefore depends on the farthest positions of all of them.
+        let types_mask =
+            if i <= WASTED_CHECK_DEPTH { u32::MAX } else { self.tetriminos.remaining_types[i] };
+        StateKey::new(self.pg.lines(), i, &self.farthest, types_mask)
+    }
+}
+
+fn backtrack(search: &mut Search, i: usize, solution: &mut [Position]) -> BacktrackResult {
     let (solution, tail_solution) = match solution.split_first_mut() {
         Some((first, tail)) => (first, tail),
         None => return NeedNewMap,
     };
 
+    let tetriminos = search.tetriminos;
     let ttype = tetriminos.types[i];
     let tsize = tetriminos.sizes[i];
     let tpiece = tetriminos.pieces[i];
     let is_last_piece_type = tetriminos.is_last_piece_type[i];
-    let saved_farthest = farthest[ttype];
+    let saved_farthest = search.farthest[ttype];
+
+    // We already explored this exact state and did not find any solution.
+    let key = match &search.table {
+        Some(table) if i != 0 && tetriminos.count - i >= CACHE_MIN_REMAINING => {
+            let key = search.state_key(i);
+            if table.contains(&key) {
+                search.stats.cache_hits += 1;
+                return Continue;
+            }
+            search.stats.cache_misses += 1;
+            Some(key)
+        }
+        _ => None,
+    };
 
     // We use the previously found farthest position for this tetriminos type
     // to start searching for the next position.
-    let mut pos = farthest[ttype];
+    let mut pos = search.farthest[ttype];
 
-    while pg.size().checked_sub(tsize.row).map_or(false, |s| pos.row <= s) {
-        while pg.size().checked_sub(tsize.col).map_or(false, |s| pos.col <= s) {
+    while search.pg.size().checked_sub(tsize.row).is_some_and(|s| pos.row <= s) {
+        while search.pg.size().checked_sub(tsize.col).is_some_and(|s| pos.col <= s) {
             // If we waste too much tiles it means that this map is not more solvable.
-            if i <= 9 && is_last_piece_type && wasted(tetriminos, pg.size(), farthest) > wastable {
-                return NeedNewMap;
+            // The farthest positions move past the solutions found when enumerating them.
+            if i <= WASTED_CHECK_DEPTH && is_last_piece_type && search.solutions.is_none() {
+                // The tiles counted as wasted may be covered by the pinned tetriminos.
+                let wasted = wasted(tetriminos, search.pg.size(), &search.farthest);
+                if wasted > search.wastable + search.pinned {
+                    let wastable = search.wastable;
+                    search.step(StepEvent::NeedNewMap { index: i, wasted, wastable });
+                    return NeedNewMap;
+                }
             }
 
-            if pg.can_write_piece(tpiece, &pos) {
-                pg.xor_piece(tpiece, &pos);
+            if search.pg.can_write_piece(tpiece, &pos) {
+                search.pg.xor_piece(tpiece, &pos);
+                search.stats.placements += 1;
+                if search.deadline.reached(search.stats.placements) {
+                    return TimedOut;
+                }
 
                 // We saved the farthest position available for the next tetrimino of the same type.
                 let jump = tetriminos.jump_columns[i];
-                farthest[ttype] = Position { row: pos.row, col: pos.col + jump };
+                search.farthest[ttype] = Position { row: pos.row, col: pos.col + jump };
+                if search.step(StepEvent::Place { index: i, position: pos }) {
+                    return NeedNewMap;
+                }
 
+                search.positions[i] = pos;
                 if i + 1 == tetriminos.count {
                     *solution = pos;
-                    return SolutionFound;
-                }
-
-                match backtrack(tetriminos, i + 1, pg, wastable, tail_solution, farthest) {
-                    NeedNewMap => return NeedNewMap,
-                    SolutionFound => {
-                        *solution = pos;
+                    if !search.record_solution() {
                         return SolutionFound;
                     }
-                    Continue => (),
+                } else {
+                    // The free tiles we just isolated will never be covered, do not go deeper.
+                    // The flood-fill is slower, it only runs when the other checks pass.
+                    let isolated = search.isolated;
+                    let pruned = if search.options.unreachable_pruning && search.unreachable(i + 1)
+                    {
+                        search.stats.unreachable_prunes += 1;
+                        true
+                    } else if search.options.flood_fill {
+                        search.isolated += search.pg.isolated_tiles_around(tpiece, &pos);
+                        let pruned = search.isolated > search.wastable;
+                        search.stats.flood_fill_prunes += u64::from(pruned);
+                        pruned
+                    } else {
+                        false
+                    };
+
+                    if pruned {
+                        if search.step(StepEvent::Prune { index: i, position: pos }) {
+                            return NeedNewMap;
+                        }
+                    } else {
+                        match backtrack(search, i + 1, tail_solution) {
+                            NeedNewMap => return NeedNewMap,
+                            TimedOut => return TimedOut,
+                            SolutionFound => {
+                                *solution = pos;
+                                return SolutionFound;
+                            }
+                            Continue => (),
+                        }
+                    }
+
+                    search.isolated = isolated;
                 }
 
-                pg.xor_piece(tpiece, &pos);
+                search.pg.xor_piece(tpiece, &pos);
+                if search.step(StepEvent::Remove { index: i, position: pos }) {
+                    return NeedNewMap;
+                }
             }
             pos.col += 1;
         }
@@ -106,7 +419,11 @@ fn backtrack(
 
     // We write back the previously found fartest position for this tetrimino type,
     // as we were not able to find a solution with our best position.
-    farthest[ttype] = saved_farthest;
+    search.farthest[ttype] = saved_farthest;
+
+    if let Some((table, key)) = search.table.as_mut().zip(key) {
+        table.insert(key);
+    }
 
     if i == 0 {
         NeedNewMap
@@ -120,213 +437,1270 @@ fn wasted(tetriminos: &Tetriminos, pg_size: usize, farthest: &[Position]) -> usi
         .iter()
         .zip(&tetriminos.is_first_occurence)
         .take(tetriminos.count)
-        .filter_map(|(far, ifo)| ifo.then(|| *far))
+        .filter(|(_, ifo)| **ifo)
+        .map(|(far, _)| *far)
         .min()
-        .unwrap_or_else(Position::default);
+        .unwrap_or_default();
 
     pos.row.saturating_sub(1) * pg_size + pos.col
 }
 
+/// Bars that all lie the same way only fit `size / 4` to a line, whatever the wasted tiles,
+/// returns `false` when the tetriminos of these types are such bars and do not fit.
+fn bars_fit(types_mask: u32, count: usize, size: usize) -> bool {
+    let bars = [Tetrimino::VerticalBar, Tetrimino::HorizontalBar].map(|t| 1 << t.ordinal());
+    !bars.contains(&types_mask) || count <= size * (size / 4)
+}
+
 fn compute_wastable(pg_size: usize, tetriminos_count: usize) -> usize {
     pg_size * pg_size - tetriminos_count * Tetrimino::TILE_COUNT
 }
 
 pub fn find_best_fit(raw_tetriminos: &[Tetrimino]) -> VisualMap {
-    let tetriminos_count = raw_tetriminos.len();
-    let mut solution = [Position::default(); NUMBER_TETRIMINOS];
-    let mut pg = Playground::from_number_tetriminos(tetriminos_count);
-    // The farthest position for a given piece type.
-    let mut farthest = [Position::default(); Tetrimino::VARIANT_COUNT];
-    let mut wastable = compute_wastable(pg.size(), tetriminos_count);
+    let options = SearchOptions::default();
+    let (map, _stats) = find_best_fit_with(raw_tetriminos, &options).expect("no timeout");
+    map
+}
+
+pub fn find_best_fit_with(
+    raw_tetriminos: &[Tetrimino],
+    options: &SearchOptions,
+) -> Result<(VisualMap, SearchStats), SearchError> {
+    find_best_fit_from(raw_tetriminos, options, 0)
+}
+
+/// Finds the best fit like `find_best_fit_with`, knowing that no map smaller than `min_size`
+/// can fit the tetriminos.
+fn find_best_fit_from(
+    raw_tetriminos: &[Tetrimino],
+    options: &SearchOptions,
+    min_size: usize,
+) -> Result<(VisualMap, SearchStats), SearchError> {
+    let mut stats = SearchStats::default();
+    let mut deadline = Deadline::after(options.timeout);
+    let (positions, size) = match options.order {
+        SearchOrder::Lexicographic => {
+            backtrack_best_fit(raw_tetriminos, options, min_size, &mut deadline, &mut stats, None)?
+        }
+        SearchOrder::AnyMinimal => {
+            let order = search_order::constraint_order(raw_tetriminos);
+            let ordered: Vec<_> = order.iter().map(|&i| raw_tetriminos[i]).collect();
+            let (ordered_positions, size) =
+                backtrack_best_fit(&ordered, options, min_size, &mut deadline, &mut stats, None)?;
+
+            (input_order(&order, ordered_positions), size)
+        }
+        SearchOrder::MostConstrainedCell => cell_search::find_best_fit(
+            raw_tetriminos,
+            options,
+            min_size,
+            &mut deadline,
+            &mut stats,
+        )?,
+    };
+
+    let solution = raw_tetriminos.iter().copied().zip(positions).collect();
+    Ok((VisualMap::new(solution, size), stats))
+}
+
+/// Finds the lexicographic best fit like `find_best_fit_with` whatever the search order,
+/// the hook is called on every step of the backtracking and can make it skip map sizes.
+pub fn watch_best_fit(
+    raw_tetriminos: &[Tetrimino],
+    options: &SearchOptions,
+    hook: &mut StepHook,
+) -> Result<(VisualMap, SearchStats), SearchError> {
+    let mut stats = SearchStats::default();
+    let mut deadline = Deadline::after(options.timeout);
+    let (positions, size) =
+        backtrack_best_fit(raw_tetriminos, options, 0, &mut deadline, &mut stats, Some(hook))?;
+
+    let solution = raw_tetriminos.iter().copied().zip(positions).collect();
+    Ok((VisualMap::new(solution, size), stats))
+}
+
+/// Writes as many tetriminos as possible in a map of this size, which is also the biggest
+/// area as they all cover four tiles. Among the best packings, the first tetriminos are
+/// preferred and written as close to the top left as possible.
+///
+/// # Panics
+///
+/// Panics if the size is bigger than `MAX_SIZE`.
+pub fn find_max_packing(
+    raw_tetriminos: &[Tetrimino],
+    size: usize,
+    options: &SearchOptions,
+) -> Result<(Packing, SearchStats), SearchError> {
+    let mut stats = SearchStats::default();
+    let mut deadline = Deadline::after(options.timeout);
+    let positions = max_packing::find_max_packing(raw_tetriminos, size, &mut deadline, &mut stats)?;
+
+    let mut written = Vec::new();
+    let mut indices = Vec::new();
+    let mut left_out = Vec::new();
+    for (i, (t, position)) in raw_tetriminos.iter().zip(positions).enumerate() {
+        match position {
+            Some(position) => {
+                written.push((*t, position));
+                indices.push(i);
+            }
+            None => left_out.push(i),
+        }
+    }
+
+    let map = VisualMap::new(written, size).with_indices(indices);
+    Ok((Packing { map, left_out }, stats))
+}
+
+/// Finds the fit in the smallest map with the lowest cost for this objective, the best fit of
+/// `find_best_fit_with` is kept when no other fit costs less. The fits are searched by a branch
+/// and bound that writes the tetriminos in the input order, whatever the search order.
+///
+/// Only fails when the timeout is reached before the best fit of `find_best_fit_with` is found,
+/// after it the best fit found so far is returned and is not `optimal`.
+pub fn find_best_fit_for(
+    raw_tetriminos: &[Tetrimino],
+    objective: &dyn Objective,
+    options: &SearchOptions,
+) -> Result<(ObjectiveFit, SearchStats), SearchError> {
+    let mut deadline = Deadline::after(options.timeout);
+    let (map, mut stats) = find_best_fit_with(raw_tetriminos, options)?;
+    let size = map.size();
+    let fit = map.tetriminos().iter().map(|(_, p)| *p).collect();
+
+    let (positions, cost, optimal) = objective_search::find_best_fit(
+        raw_tetriminos,
+        objective,
+        size,
+        fit,
+        &mut deadline,
+        &mut stats,
+    );
+
+    let solution = raw_tetriminos.iter().copied().zip(positions).collect();
+    Ok((ObjectiveFit { map: VisualMap::new(solution, size), cost, optimal }, stats))
+}
+
+/// Writes the tetriminos in as few maps of this size as possible, every map keeps the letters
+/// of the tetriminos in the input. Returns `None` if a tetrimino does not fit in a map.
+///
+/// A first packing is found at once, when the timeout is reached the best packing found
+/// so far is returned and is not `optimal`.
+///
+/// # Panics
+///
+/// Panics if the size is bigger than `MAX_SIZE`.
+pub fn find_bin_packing(
+    raw_tetriminos: &[Tetrimino],
+    size: usize,
+    options: &SearchOptions,
+) -> (Option<Bins>, SearchStats) {
+    let mut stats = SearchStats::default();
+    let mut deadline = Deadline::after(options.timeout);
+    let (placements, optimal) =
+        match bin_packing::find_bin_packing(raw_tetriminos, size, &mut deadline, &mut stats) {
+            Some(found) => found,
+            None => return (None, stats),
+        };
+
+    let count = placements.iter().map(|(map, _)| map + 1).max().unwrap_or(0);
+    let maps = (0..count).map(|m| {
+        let written = raw_tetriminos.iter().zip(&placements).enumerate();
+        let written: Vec<_> = written.filter(|(_, (_, (map, _)))| *map == m).collect();
+        let indices = written.iter().map(|(i, _)| *i).collect();
+        let tetriminos = written.into_iter().map(|(_, (t, (_, p)))| (*t, *p)).collect();
+        VisualMap::new(tetriminos, size).with_indices(indices)
+    });
+    (Some(Bins { maps: maps.collect(), optimal }), stats)
+}
+
+/// Finds the smallest map where the pinned tetriminos are written where their pins say,
+/// every pin of a tetrimino must be followed. The pinned tetriminos are tried row by row
+/// and the other ones are written around them like `SearchOrder::Lexicographic` does,
+/// whatever the search order. Returns `None` when the pins cannot be followed in any map.
+///
+/// # Panics
+///
+/// Panics if a pin refers to a tetrimino that does not exist.
+pub fn find_best_fit_pinned(
+    raw_tetriminos: &[Tetrimino],
+    pins: &[(usize, Pin)],
+    options: &SearchOptions,
+) -> Result<(Option<VisualMap>, SearchStats), SearchError> {
+    assert!(
+        pins.iter().all(|(i, _)| *i < raw_tetriminos.len()),
+        "the pinned tetrimino does not exist"
+    );
+
+    let mut stats = SearchStats::default();
+    let mut deadline = Deadline::after(options.timeout);
+    let found =
+        pinned_search::find_best_fit(raw_tetriminos, pins, options, &mut deadline, &mut stats)?;
+
+    let map = found.map(|(positions, size)| {
+        let solution = raw_tetriminos.iter().copied().zip(positions).collect();
+        VisualMap::new(solution, size)
+    });
+    Ok((map, stats))
+}
+
+/// Writes the tetriminos in a map of exactly this size, if they fit in it.
+///
+/// # Panics
+///
+/// Panics if the size is bigger than `MAX_SIZE`.
+pub fn solve_at_size(raw_tetriminos: &[Tetrimino], size: usize) -> Option<VisualMap> {
+    let options = SearchOptions::default();
+    let (map, _stats) = solve_at_size_with(raw_tetriminos, size, &options).expect("no timeout");
+    map
+}
+
+/// Writes the tetriminos in a map of exactly this size in the search order of the options.
+///
+/// # Panics
+///
+/// Panics if the size is bigger than `MAX_SIZE`.
+pub fn solve_at_size_with(
+    raw_tetriminos: &[Tetrimino],
+    size: usize,
+    options: &SearchOptions,
+) -> Result<(Option<VisualMap>, SearchStats), SearchError> {
+    let mut stats = SearchStats::default();
+    let mut deadline = Deadline::after(options.timeout);
+    let positions = fit_at_size(raw_tetriminos, size, options, &mut deadline, &mut stats)?;
+
+    let map = positions.map(|positions| {
+        let solution = raw_tetriminos.iter().copied().zip(positions).collect();
+        VisualMap::new(solution, size)
+    });
+    Ok((map, stats))
+}
+
+/// Finds the sizes of the maps the tetriminos fit in, from the smallest one up to `max_size`,
+/// or `None` if they do not even fit in a map of `max_size`.
+///
+/// The sizes are tried from `max_size` down, so that the only map searched in vain is the one
+/// just under the smallest size, a close upper bound makes it faster.
+///
+/// # Panics
+///
+/// Panics if `max_size` is bigger than `MAX_SIZE`.
+pub fn feasible_range(
+    raw_tetriminos: &[Tetrimino],
+    max_size: usize,
+    options: &SearchOptions,
+) -> Result<(Option<RangeInclusive<usize>>, SearchStats), SearchError> {
+    let mut stats = SearchStats::default();
+    let mut deadline = Deadline::after(options.timeout);
+    let mut fits = |size| fit_at_size(raw_tetriminos, size, options, &mut deadline, &mut stats);
+
+    if fits(max_size)?.is_none() {
+        return Ok((None, stats));
+    }
+    let mut min_size = max_size;
+    while min_size > 0 && fits(min_size - 1)?.is_some() {
+        min_size -= 1;
+    }
+    Ok((Some(min_size..=max_size), stats))
+}
+
+/// Puts back in the input order the positions of the tetriminos written in this order.
+fn input_order(order: &[usize], ordered_positions: Vec<Position>) -> Vec<Position> {
+    let mut positions = vec![Position::default(); order.len()];
+    order.iter().zip(ordered_positions).for_each(|(&i, pos)| positions[i] = pos);
+    positions
+}
+
+/// Writes the tetriminos in a map of exactly this size in the search order of the options.
+fn fit_at_size(
+    raw_tetriminos: &[Tetrimino],
+    size: usize,
+    options: &SearchOptions,
+    deadline: &mut Deadline,
+    stats: &mut SearchStats,
+) -> Result<Option<Vec<Position>>, SearchError> {
+    match options.order {
+        SearchOrder::Lexicographic => {
+            let tetriminos = Tetriminos::from_tetriminos(raw_tetriminos);
+            backtrack_at_size(&tetriminos, size, options, deadline, stats, None, None)
+        }
+        SearchOrder::AnyMinimal => {
+            let order = search_order::constraint_order(raw_tetriminos);
+            let ordered: Vec<_> = order.iter().map(|&i| raw_tetriminos[i]).collect();
+            let tetriminos = Tetriminos::from_tetriminos(&ordered);
+            let ordered_positions =
+                backtrack_at_size(&tetriminos, size, options, deadline, stats, None, None)?;
+
+            Ok(ordered_positions.map(|positions| input_order(&order, positions)))
+        }
+        SearchOrder::MostConstrainedCell => {
+            cell_search::find_fit_at_size(raw_tetriminos, options, size, deadline, stats)
+        }
+    }
+}
+
+/// Finds up to `limit` of the fits in the smallest map, in lexicographic order
+/// whatever the search order, the first one is the one of `find_best_fit_with`.
+pub fn find_fits(
+    raw_tetriminos: &[Tetrimino],
+    options: &SearchOptions,
+    limit: usize,
+) -> Result<(Vec<VisualMap>, SearchStats), SearchError> {
+    let mut stats = SearchStats::default();
+    let mut deadline = Deadline::after(options.timeout);
     let tetriminos = Tetriminos::from_tetriminos(raw_tetriminos);
+    let mut size = Playground::from_number_tetriminos(tetriminos.count).size();
+    let mut solutions = Vec::new();
+
+    if limit == 0 {
+        return Ok((Vec::new(), stats));
+    }
+    if tetriminos.count == 0 {
+        return Ok((vec![VisualMap::new(Vec::new(), size)], stats));
+    }
+
+    while solutions.is_empty() {
+        let enumerate = Some((&mut solutions, limit));
+        backtrack_at_size(&tetriminos, size, options, &mut deadline, &mut stats, None, enumerate)?;
+        size += 1;
+    }
 
-    eprintln!("Try to fit {} tetriminos in a {} sized map.", tetriminos_count, pg.size());
-    while backtrack(
-        &tetriminos,
-        0,
-        &mut pg,
-        wastable,
-        &mut solution[..tetriminos_count],
-        &mut farthest,
-    ) == NeedNewMap
-    {
-        pg = Playground::from_size(pg.size() + 1);
-        wastable = compute_wastable(pg.size(), tetriminos_count);
-        farthest.fill_with(Position::default);
-        eprintln!("Try to fit {} tetriminos in a {} sized map.", tetriminos_count, pg.size());
-    }
-
-    let solution = raw_tetriminos.iter().copied().zip(solution.iter().copied()).collect();
-    VisualMap::new(solution, pg.size())
+    let maps = solutions.into_iter().map(|positions| {
+        let solution = raw_tetriminos.iter().copied().zip(positions).collect();
+        VisualMap::new(solution, size - 1)
+    });
+    Ok((maps.collect(), stats))
+}
+
+/// Writes the tetriminos in order and returns their positions along with the size of the map.
+fn backtrack_best_fit(
+    raw_tetriminos: &[Tetrimino],
+    options: &SearchOptions,
+    min_size: usize,
+    deadline: &mut Deadline,
+    stats: &mut SearchStats,
+    mut hook: Option<&mut StepHook>,
+) -> Result<(Vec<Position>, usize), SearchError> {
+    let tetriminos = Tetriminos::from_tetriminos(raw_tetriminos);
+    let mut size = Playground::from_number_tetriminos(tetriminos.count).size().max(min_size);
+
+    loop {
+        let hook = hook.as_deref_mut();
+        if let Some(positions) =
+            backtrack_at_size(&tetriminos, size, options, deadline, stats, hook, None)?
+        {
+            return Ok((positions, size));
+        }
+        size += 1;
+    }
+}
+
+/// Writes the tetriminos in order in a map of exactly this size, if they fit in it.
+fn backtrack_at_size(
+    tetriminos: &Tetriminos,
+    size: usize,
+    options: &SearchOptions,
+    deadline: &mut Deadline,
+    stats: &mut SearchStats,
+    hook: Option<&mut StepHook>,
+    solutions: Option<(&mut Vec<Vec<Position>>, usize)>,
+) -> Result<Option<Vec<Position>>, SearchError> {
+    if tetriminos.count == 0 {
+        return Ok(Some(Vec::new()));
+    }
+    if size * size < tetriminos.count * Tetrimino::TILE_COUNT {
+        return Ok(None);
+    }
+    if !bars_fit(tetriminos.remaining_types[0], tetriminos.count, size) {
+        return Ok(None);
+    }
+
+    let mut search = Search::new(tetriminos, options, Playground::from_size(size), deadline, stats);
+    search.hook = hook;
+    if solutions.is_some() {
+        // The dead states may have been reached after finding solutions when enumerating them.
+        search.table = None;
+        search.solutions = solutions;
+    }
+
+    search.step(StepEvent::NewMap { size });
+    search.run()
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, Instant};
+    use std::{env, fs};
+
+    use proptest::prelude::*;
+
     use super::*;
 
-    const NOT_VALID_0: &str = include_str!("../tests/not_valid_0.fillit");
-    const NOT_VALID_BAD_ENDLINE: &str = include_str!("../tests/not_valid_bad_endline.fillit");
-    const NOT_VALID_BAD_PIECES: &str = include_str!("../tests/not_valid_bad_pieces.fillit");
     const NOT_VALID_COMMA: &str = include_str!("../tests/not_valid_comma.fillit");
-    const NOT_VALID_EMPTY: &str = include_str!("../tests/not_valid_empty.fillit");
-    const NOT_VALID_ENDLINE: &str = include_str!("../tests/not_valid_endline.fillit");
-    const NOT_VALID_LINE_LEN: &str = include_str!("../tests/not_valid_line_len.fillit");
-    const NOT_VALID_TOO_BIG_27: &str = include_str!("../tests/not_valid_too_big_27.fillit");
-    const NOT_VALID_X: &str = include_str!("../tests/not_valid_X.fillit");
-    const NOT_VALIDS: &[&str] = &[
-        NOT_VALID_0,
-        NOT_VALID_BAD_ENDLINE,
-        NOT_VALID_BAD_PIECES,
-        NOT_VALID_COMMA,
-        NOT_VALID_EMPTY,
-        NOT_VALID_ENDLINE,
-        NOT_VALID_LINE_LEN,
-        NOT_VALID_TOO_BIG_27,
-        NOT_VALID_X,
-    ];
-
-    const VALID_0: &str = include_str!("../tests/valid_0.fillit");
     const VALID_1: &str = include_str!("../tests/valid_1.fillit");
-    const VALID_2: &str = include_str!("../tests/valid_2.fillit");
-    const VALID_3: &str = include_str!("../tests/valid_3.fillit");
-    const VALID_4: &str = include_str!("../tests/valid_4.fillit");
-    const VALID_EACH_PIECE: &str = include_str!("../tests/valid_each_piece.fillit");
-    const VALID_HARD: &str = include_str!("../tests/valid_hard.fillit");
-    const VALID_HARD_FORUM: &str = include_str!("../tests/valid_hard_forum.fillit");
-    const VALID_HARD_FORUM_10: &str = include_str!("../tests/valid_hard_forum_10.fillit");
-    const VALID_HARD_FORUM_11: &str = include_str!("../tests/valid_hard_forum_11.fillit");
-    const VALID_HARD_FORUM_12: &str = include_str!("../tests/valid_hard_forum_12.fillit");
-    const VALID_HARD_FORUM_13: &str = include_str!("../tests/valid_hard_forum_13.fillit");
-    const VALID_HARD_FORUM_14: &str = include_str!("../tests/valid_hard_forum_14.fillit");
-    const VALID_HARD_FORUM_15: &str = include_str!("../tests/valid_hard_forum_15.fillit");
-    const VALID_HARD_FORUM_16: &str = include_str!("../tests/valid_hard_forum_16.fillit");
-    const VALID_HARD_FORUM_17: &str = include_str!("../tests/valid_hard_forum_17.fillit");
-    const VALID_HARD_FORUM_18: &str = include_str!("../tests/valid_hard_forum_18.fillit");
-    const VALID_HARD_FORUM_19: &str = include_str!("../tests/valid_hard_forum_19.fillit");
-    const VALID_HARD_FORUM_20: &str = include_str!("../tests/valid_hard_forum_20.fillit");
-    const VALID_HARD_FORUM_21: &str = include_str!("../tests/valid_hard_forum_21.fillit");
-    const VALID_HARD_FORUM_22: &str = include_str!("../tests/valid_hard_forum_22.fillit");
     const VALID_HARD_FORUM_23: &str = include_str!("../tests/valid_hard_forum_23.fillit");
-    const VALID_I_16: &str = include_str!("../tests/valid_I_16.fillit");
-    const VALID_I_25: &str = include_str!("../tests/valid_I_25.fillit");
-    const VALID_I_26: &str = include_str!("../tests/valid_I_26.fillit");
-    const VALIDS: &[&str] = &[
-        VALID_0,
-        VALID_1,
-        VALID_2,
-        VALID_3,
-        VALID_4,
-        VALID_EACH_PIECE,
-        VALID_HARD,
-        VALID_HARD_FORUM,
-        VALID_HARD_FORUM_10,
-        VALID_HARD_FORUM_11,
-        VALID_HARD_FORUM_12,
-        VALID_HARD_FORUM_13,
-        VALID_HARD_FORUM_14,
-        VALID_HARD_FORUM_15,
-        VALID_HARD_FORUM_16,
-        VALID_HARD_FORUM_17,
-        VALID_HARD_FORUM_18,
-        VALID_HARD_FORUM_19,
-        VALID_HARD_FORUM_20,
-        VALID_HARD_FORUM_21,
-        VALID_HARD_FORUM_22,
-        VALID_HARD_FORUM_23,
-        VALID_I_16,
-        VALID_I_25,
-        VALID_I_26,
-    ];
-
-    const ANSWER_0: &str = include_str!("../tests/answers/valid_0.answer");
     const ANSWER_1: &str = include_str!("../tests/answers/valid_1.answer");
-    const ANSWER_2: &str = include_str!("../tests/answers/valid_2.answer");
-    const ANSWER_3: &str = include_str!("../tests/answers/valid_3.answer");
-    const ANSWER_4: &str = include_str!("../tests/answers/valid_4.answer");
-    const ANSWER_EACH_PIECE: &str = include_str!("../tests/answers/valid_each_piece.answer");
-    const ANSWER_HARD: &str = include_str!("../tests/answers/valid_hard.answer");
-    const ANSWER_HARD_FORUM: &str = include_str!("../tests/answers/valid_hard_forum.answer");
-    const ANSWER_HARD_FORUM_10: &str = include_str!("../tests/answers/valid_hard_forum_10.answer");
-    const ANSWER_HARD_FORUM_11: &str = include_str!("../tests/answers/valid_hard_forum_11.answer");
-    const ANSWER_HARD_FORUM_12: &str = include_str!("../tests/answers/valid_hard_forum_12.answer");
-    const ANSWER_HARD_FORUM_13: &str = include_str!("../tests/answers/valid_hard_forum_13.answer");
-    const ANSWER_HARD_FORUM_14: &str = include_str!("../tests/answers/valid_hard_forum_14.answer");
-    const ANSWER_HARD_FORUM_15: &str = include_str!("../tests/answers/valid_hard_forum_15.answer");
-    const ANSWER_HARD_FORUM_16: &str = include_str!("../tests/answers/valid_hard_forum_16.answer");
-    const ANSWER_HARD_FORUM_17: &str = include_str!("../tests/answers/valid_hard_forum_17.answer");
-    const ANSWER_HARD_FORUM_18: &str = include_str!("../tests/answers/valid_hard_forum_18.answer");
-    const ANSWER_HARD_FORUM_19: &str = include_str!("../tests/answers/valid_hard_forum_19.answer");
-    const ANSWER_HARD_FORUM_20: &str = include_str!("../tests/answers/valid_hard_forum_20.answer");
-    const ANSWER_HARD_FORUM_21: &str = include_str!("../tests/answers/valid_hard_forum_21.answer");
-    const ANSWER_HARD_FORUM_22: &str = include_str!("../tests/answers/valid_hard_forum_22.answer");
-    const ANSWER_HARD_FORUM_23: &str = include_str!("../tests/answers/valid_hard_forum_23.answer");
-    const ANSWER_I_16: &str = include_str!("../tests/answers/valid_I_16.answer");
-    const ANSWER_I_26: &str = include_str!("../tests/answers/valid_I_26.answer");
-
-    const EASY_MAPS_ANSWERS: &[(&str, &str)] = &[
-        (VALID_0, ANSWER_0),
-        (VALID_1, ANSWER_1),
-        (VALID_2, ANSWER_2),
-        (VALID_3, ANSWER_3),
-        (VALID_4, ANSWER_4),
-        (VALID_EACH_PIECE, ANSWER_EACH_PIECE),
-        (VALID_HARD, ANSWER_HARD),
-        (VALID_HARD_FORUM_10, ANSWER_HARD_FORUM_10),
-        (VALID_HARD_FORUM_11, ANSWER_HARD_FORUM_11),
-        (VALID_HARD_FORUM_12, ANSWER_HARD_FORUM_12),
-        (VALID_HARD_FORUM_13, ANSWER_HARD_FORUM_13),
-        (VALID_HARD_FORUM_14, ANSWER_HARD_FORUM_14),
-        (VALID_HARD_FORUM_15, ANSWER_HARD_FORUM_15),
-        (VALID_HARD_FORUM_16, ANSWER_HARD_FORUM_16),
-        (VALID_HARD_FORUM_17, ANSWER_HARD_FORUM_17),
-        (VALID_HARD_FORUM_21, ANSWER_HARD_FORUM_21),
-        (VALID_HARD_FORUM_22, ANSWER_HARD_FORUM_22),
-        (VALID_I_16, ANSWER_I_16),
-        (VALID_I_26, ANSWER_I_26),
-    ];
 
-    const HARD_MAPS_ANSWERS: &[(&str, &str)] = &[
-        (VALID_HARD_FORUM, ANSWER_HARD_FORUM),
-        (VALID_HARD_FORUM_18, ANSWER_HARD_FORUM_19),
-        (VALID_HARD_FORUM_18, ANSWER_HARD_FORUM_18),
-        (VALID_HARD_FORUM_20, ANSWER_HARD_FORUM_20),
-        (VALID_HARD_FORUM_23, ANSWER_HARD_FORUM_23),
+    /// A `tests/*.fillit` input and its `tests/answers/*.answer`, if there is one.
+    struct Case {
+        name: String,
+        input: String,
+        answer: Option<String>,
+    }
+
+    impl Case {
+        fn is_valid(&self) -> bool {
+            !self.name.starts_with("not_valid_")
+        }
+
+        fn answer_path(&self) -> PathBuf {
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join(format!("tests/answers/{}.answer", self.name))
+        }
+    }
+
+    /// Discovers every input of the tests directory, sorted by name.
+    fn corpus() -> Vec<Case> {
+        let pattern = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/*.fillit");
+        let mut cases: Vec<_> = glob::glob(&pattern.to_string_lossy())
+            .unwrap()
+            .map(|path| {
+                let path = path.unwrap();
+                let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+                let input = fs::read_to_string(&path).unwrap();
+                let mut case = Case { name, input, answer: None };
+                case.answer = fs::read_to_string(case.answer_path()).ok();
+                case
+            })
+            .collect();
+        cases.sort_by(|a, b| a.name.cmp(&b.name));
+        assert!(!cases.is_empty(), "no inputs found in the tests directory");
+        cases
+    }
+
+    /// The valid inputs of the corpus that have an answer.
+    fn answered() -> Vec<(String, Vec<Tetrimino>, String)> {
+        corpus()
+            .into_iter()
+            .filter(Case::is_valid)
+            .filter_map(|case| {
+                let Case { name, input, answer } = case;
+                let tetriminos = parse_tetriminos(&input).unwrap();
+                answer.map(|answer| (name, tetriminos, answer))
+            })
+            .collect()
+    }
+
+    /// The time given to each search, `FILLIT_TEST_BUDGET` seconds or one second.
+    fn time_budget() -> Duration {
+        let seconds = env::var("FILLIT_TEST_BUDGET").ok().map(|s| s.parse().unwrap());
+        Duration::from_secs_f64(seconds.unwrap_or(1.0))
+    }
+
+    /// The inputs that may take longer than the time budget, every other one must be solved.
+    const HARD_INPUTS: &[&str] = &[
+        "valid_I_25",
+        "valid_hard_forum",
+        "valid_hard_forum_18",
+        "valid_hard_forum_19",
+        "valid_hard_forum_20",
+        "valid_hard_forum_23",
     ];
 
+    /// Solves within the time budget, the hard maps that take longer are skipped.
+    fn solve_within(
+        name: &str,
+        tetriminos: &[Tetrimino],
+        options: &SearchOptions,
+    ) -> Option<VisualMap> {
+        let options = SearchOptions { timeout: Some(time_budget()), ..options.clone() };
+        match find_best_fit_with(tetriminos, &options) {
+            Ok((map, _stats)) => Some(map),
+            Err(SearchError::TimedOut) => {
+                assert!(
+                    HARD_INPUTS.contains(&name),
+                    "{} takes more than {:?} and is not a hard input",
+                    name,
+                    time_budget()
+                );
+                eprintln!("skipping {}, it takes more than {:?}", name, time_budget());
+                None
+            }
+        }
+    }
+
     #[test]
-    fn valid_maps() {
-        for (i, map) in VALIDS.iter().enumerate() {
-            eprintln!("testing map #{}", i);
-            parse_tetriminos(map).unwrap();
+    fn golden_answers() {
+        let bless = env::var_os("BLESS").is_some_and(|v| v == "1");
+        for case in corpus() {
+            eprintln!("testing {}", case.name);
+            if !case.is_valid() {
+                parse_tetriminos(&case.input).unwrap_err();
+                continue;
+            }
+
+            let tetriminos = parse_tetriminos(&case.input).unwrap();
+            let options = SearchOptions::default();
+            let map = match solve_within(&case.name, &tetriminos, &options) {
+                Some(map) => map.to_string(),
+                None => continue,
+            };
+
+            if bless {
+                if case.answer.as_ref() != Some(&map) {
+                    fs::write(case.answer_path(), &map).unwrap();
+                    eprintln!("blessed {}", case.answer_path().display());
+                }
+            } else {
+                let name = &case.name;
+                let answer = case.answer.as_ref().unwrap_or_else(|| {
+                    panic!("{} has no answer, run the tests with BLESS=1 to write it", name)
+                });
+                assert_eq!(&map, answer, "{}", name);
+            }
         }
     }
 
     #[test]
-    fn invalid_maps() {
-        for (i, map) in NOT_VALIDS.iter().enumerate() {
-            eprintln!("testing map #{}", i);
-            parse_tetriminos(map).unwrap_err();
+    fn strict_maps() {
+        let options = ParseOptions { mode: ParseMode::Strict, ..ParseOptions::default() };
+        for case in corpus() {
+            eprintln!("testing {}", case.name);
+            let result = parse_tetriminos_with(&case.input, &options);
+            assert_eq!(result.is_ok(), case.is_valid(), "{}", case.name);
         }
     }
 
     #[test]
-    fn valid_easy_answer_maps() {
-        for (i, (map, answer)) in EASY_MAPS_ANSWERS.iter().enumerate() {
+    fn strict_and_lenient_maps() {
+        let strict = ParseOptions { mode: ParseMode::Strict, ..ParseOptions::default() };
+        let lenient = ParseOptions { mode: ParseMode::Lenient, ..ParseOptions::default() };
+        let cases = [
+            (VALID_1.replace('\n', "\r\n"), ParseError::InvalidCharacter('\r')),
+            (VALID_1.replace('\n', " \t\n"), ParseError::InvalidCharacter(' ')),
+            (VALID_1.replace("\n\n", "\n\n\n"), ParseError::TooManyLines),
+            (format!("{}\n", VALID_1), ParseError::TooManyLines),
+            (VALID_1.trim_end().to_string(), ParseError::MissingNewline),
+        ];
+
+        let expected = parse_tetriminos(VALID_1).unwrap();
+        for (i, (map, error)) in cases.iter().enumerate() {
             eprintln!("testing map #{}", i);
-            let tetriminos = parse_tetriminos(map).unwrap();
-            let map = find_best_fit(&tetriminos);
-            assert_eq!(&map.to_string(), answer);
+            let err = parse_tetriminos_with(map, &strict).unwrap_err();
+            assert_eq!(err.downcast_ref::<ParseError>(), Some(error));
+            assert_eq!(parse_tetriminos_with(map, &lenient).unwrap(), expected);
         }
     }
 
     #[test]
-    #[ignore]
-    fn valid_hard_answer_maps() {
-        for (i, (map, answer)) in HARD_MAPS_ANSWERS.iter().enumerate() {
-            eprintln!("testing map #{}", i);
-            let tetriminos = parse_tetriminos(map).unwrap();
-            let map = find_best_fit(&tetriminos);
-            assert_eq!(&map.to_string(), answer);
+    fn custom_chars_maps() {
+        let options = ParseOptions { empty: ',', ..ParseOptions::default() };
+        let tetriminos = parse_tetriminos_with(NOT_VALID_COMMA, &options).unwrap();
+        assert_eq!(tetriminos, [Tetrimino::HorizontalBar, Tetrimino::Square]);
+
+        let binary = VALID_1.replace('.', "0").replace('#', "1");
+        let options = ParseOptions { empty: '0', full: '1', ..ParseOptions::default() };
+        let tetriminos = parse_tetriminos_with(&binary, &options).unwrap();
+        assert_eq!(tetriminos, parse_tetriminos(VALID_1).unwrap());
+
+        // The spaces around the tiles are only ignored when they are not tiles.
+        let spaced = VALID_1.replace('.', " ");
+        let options = ParseOptions { empty: ' ', ..ParseOptions::default() };
+        assert_eq!(parse_tetriminos_with(&spaced, &options).unwrap(), tetriminos);
+
+        let map = find_best_fit(&tetriminos).with_empty(' ');
+        assert_eq!(map.to_string(), ANSWER_1.replace('.', " "));
+    }
+
+    #[test]
+    fn compact_maps() {
+        let tetriminos = parse_compact("L90\n  T \n\n0xcc00\n00f0\nJ270\n").unwrap();
+        let expected = [
+            Tetrimino::NormalLRotate90,
+            Tetrimino::Podium,
+            Tetrimino::Square,
+            Tetrimino::HorizontalBar,
+            Tetrimino::MirrorLRotate270,
+        ];
+        assert_eq!(tetriminos, expected);
+
+        for (name, tetriminos, _answer) in answered() {
+            eprintln!("testing {}", name);
+            let compact = to_compact(&tetriminos);
+            assert_eq!(parse_compact(&compact).unwrap(), tetriminos);
+            let fillit = to_fillit(&parse_compact(&compact).unwrap());
+            let strict = ParseOptions { mode: ParseMode::Strict, ..ParseOptions::default() };
+            assert_eq!(parse_tetriminos_with(&fillit, &strict).unwrap(), tetriminos);
+        }
+
+        for (text, error) in [("", ParseError::NoTetriminos), ("T\nX\n", ParseError::UnknownPiece)]
+        {
+            let err = parse_compact(text).unwrap_err();
+            assert_eq!(err.downcast_ref::<ParseError>(), Some(&error));
+        }
+        parse_compact("0x8421\n").unwrap_err();
+    }
+
+    #[test]
+    fn formats_maps() {
+        let options = ParseOptions::default();
+        for case in corpus().into_iter().filter(Case::is_valid) {
+            eprintln!("testing {}", case.name);
+            let tetriminos = parse_tetriminos(&case.input).unwrap();
+            assert_eq!(Format::detect(&case.input, &options), Format::Fillit);
+            if let Some(answer) = &case.answer {
+                assert_eq!(Format::detect(answer, &options), Format::Answer);
+                assert_eq!(parse_answer(answer, '.').unwrap(), tetriminos);
+
+                let spaced = answer.replace('.', " ");
+                let spaces = ParseOptions { empty: ' ', ..ParseOptions::default() };
+                assert_eq!(Format::detect(&spaced, &spaces), Format::Answer);
+                assert_eq!(parse_answer(&spaced, ' ').unwrap(), tetriminos);
+            }
+
+            for format in [Format::Fillit, Format::Compact, Format::Json] {
+                let text = format.write(&tetriminos, &SearchOptions::default()).unwrap();
+                assert_eq!(Format::detect(&text, &options), format);
+                assert_eq!(format.parse(&text, &options).unwrap(), tetriminos);
+            }
+        }
+
+        let json = r####"["L90", 52224, ["..#.", "###.", "....", "...."]]"####;
+        let expected = [Tetrimino::NormalLRotate90, Tetrimino::Square, Tetrimino::NormalLRotate90];
+        assert_eq!(parse_json(json).unwrap(), expected);
+        assert_eq!(Format::detect("T\nO\nL\n", &options), Format::Compact);
+
+        let err = parse_json(r#"{"pieces": []}"#).unwrap_err();
+        assert_eq!(err.downcast_ref::<ParseError>(), Some(&ParseError::InvalidJson));
+        let err = parse_answer("AA.A\nA...\nBB..\nBB..\n", '.').unwrap_err();
+        assert_eq!(err.downcast_ref::<ParseError>(), Some(&ParseError::InvalidTetrimino));
+
+        let tetriminos = parse_tetriminos(VALID_HARD_FORUM_23).unwrap();
+        let options =
+            SearchOptions { timeout: Some(Duration::from_millis(50)), ..SearchOptions::default() };
+        assert_eq!(Format::Answer.write(&tetriminos, &options), Err(SearchError::TimedOut));
+    }
+
+    #[test]
+    fn answer_maps_flood_fill() {
+        let options = SearchOptions { flood_fill: true, ..SearchOptions::default() };
+        for (name, tetriminos, answer) in answered() {
+            eprintln!("testing {}", name);
+            if let Some(map) = solve_within(&name, &tetriminos, &options) {
+                assert_eq!(map.to_string(), answer, "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn isolated_large_regions() {
+        // The square splits the 32 free tiles under the bar into regions of 10 and 18 tiles.
+        let mut pg = Playground::from_size(6);
+        let (bar, square) = (Tetrimino::HorizontalBar.piece(), Tetrimino::Square.piece());
+        pg.xor_piece(bar, &Position { row: 2, col: 0 });
+        assert_eq!(pg.isolated_tiles_around(bar, &Position { row: 2, col: 0 }), 0);
+        pg.xor_piece(square, &Position { row: 1, col: 4 });
+        assert_eq!(pg.isolated_tiles_around(square, &Position { row: 1, col: 4 }), 4);
+    }
+
+    #[test]
+    fn answer_maps_cache() {
+        let options = SearchOptions { cache_memory: Some(1024 * 1024), ..SearchOptions::default() };
+        for (name, tetriminos, answer) in answered() {
+            eprintln!("testing {}", name);
+            if let Some(map) = solve_within(&name, &tetriminos, &options) {
+                assert_eq!(map.to_string(), answer, "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn map_outputs() {
+        let map = find_best_fit(&parse_tetriminos(VALID_1).unwrap());
+        let json: serde_json::Value = serde_json::from_str(&map.to_json()).unwrap();
+        assert_eq!(json["size"], 4);
+        assert_eq!(json["placements"][0]["letter"], "A");
+        assert_eq!(json["placements"][0]["tetrimino"], "L180");
+        assert_eq!(json["placements"][1]["row"], 3);
+
+        let svg = map.to_svg();
+        assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<text ").count(), 16);
+    }
+
+    #[test]
+    fn no_tetriminos() {
+        for order in [SearchOrder::Lexicographic, SearchOrder::MostConstrainedCell] {
+            let options = SearchOptions { order, ..SearchOptions::default() };
+            let (map, _stats) = find_best_fit_with(&[], &options).unwrap();
+            assert_eq!(map.size(), 0);
+            assert_eq!(map.to_string(), "");
+        }
+    }
+
+    #[test]
+    fn biggest_playground_bottom_line() {
+        let mut pg = Playground::from_size(16);
+        let piece = Tetrimino::HorizontalBar.piece();
+        let pos = Position { col: 12, row: 15 };
+        assert!(pg.can_write_piece(piece, &pos));
+        pg.xor_piece(piece, &pos);
+        assert!(!pg.can_write_piece(piece, &pos));
+    }
+
+    #[test]
+    fn solver_options() {
+        let tetriminos = parse_tetriminos(VALID_1).unwrap();
+        let mut solver = Solver::new(SearchOptions::default());
+        tetriminos.iter().for_each(|t| solver.push(*t).unwrap());
+        assert_eq!(solver.solve().unwrap().0.to_string(), find_best_fit(&tetriminos).to_string());
+
+        let options = SearchOptions { order: SearchOrder::AnyMinimal, ..SearchOptions::default() };
+        let (expected, _stats) = find_best_fit_with(&tetriminos, &options).unwrap();
+        solver.set_options(options);
+        assert!(solver.solution().is_none());
+        assert_eq!(solver.solve().unwrap().0.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn weighted_cost_saturates() {
+        let weighted = Weighted {
+            objectives: vec![(usize::MAX, Box::new(TopLeftHoles)), (2, Box::new(BoundingHeight))],
+        };
+        let fit = [(Tetrimino::Square, Some(Position { row: 2, col: 2 }))];
+        assert_eq!(weighted.cost(4, &fit), usize::MAX);
+    }
+
+    #[test]
+    fn objective_fit_timeout() {
+        let tetriminos =
+            parse_tetriminos(include_str!("../tests/valid_identical_T_26.fillit")).unwrap();
+        let (map, _stats) = find_best_fit_with(&tetriminos, &SearchOptions::default()).unwrap();
+        let fit: Vec<_> = map.tetriminos().iter().map(|(t, p)| (*t, Some(*p))).collect();
+        let first_cost = FamilyAdjacency.cost(map.size(), &fit);
+
+        let options =
+            SearchOptions { timeout: Some(Duration::from_millis(50)), ..SearchOptions::default() };
+        let (found, _stats) = find_best_fit_for(&tetriminos, &FamilyAdjacency, &options).unwrap();
+        assert!(!found.optimal);
+        assert_legal(&found.map);
+        let fit: Vec<_> = found.map.tetriminos().iter().map(|(t, p)| (*t, Some(*p))).collect();
+        assert_eq!(FamilyAdjacency.cost(found.map.size(), &fit), found.cost);
+        assert!(found.cost <= first_cost);
+    }
+
+    #[test]
+    fn bin_packing_timeout() {
+        let tetriminos = parse_tetriminos(VALID_HARD_FORUM_23).unwrap();
+        let options =
+            SearchOptions { timeout: Some(Duration::from_millis(50)), ..SearchOptions::default() };
+        let (bins, _stats) = find_bin_packing(&tetriminos, 5, &options);
+        let bins = bins.unwrap();
+        assert!(!bins.optimal);
+        bins.maps.iter().for_each(assert_legal);
+        let count: usize = bins.maps.iter().map(|m| m.tetriminos().len()).sum();
+        assert_eq!(count, tetriminos.len());
+    }
+
+    #[test]
+    fn hard_map_timeout() {
+        let timeout = Some(Duration::from_millis(50));
+        let options = SearchOptions { timeout, ..SearchOptions::default() };
+        let tetriminos = parse_tetriminos(VALID_HARD_FORUM_23).unwrap();
+        let start = Instant::now();
+        let result = find_best_fit_with(&tetriminos, &options);
+        assert_eq!(result.err(), Some(SearchError::TimedOut));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn watch_steps() {
+        let tetriminos = parse_tetriminos(VALID_1).unwrap();
+        let mut placed = vec![None; tetriminos.len()];
+        let mut sizes = Vec::new();
+        let mut hook = |event, farthest: &[Position]| {
+            assert_eq!(farthest.len(), Tetrimino::VARIANT_COUNT);
+            match event {
+                StepEvent::NewMap { size } => {
+                    sizes.push(size);
+                    placed.iter_mut().for_each(|p| *p = None);
+                }
+                StepEvent::Place { index, position } => {
+                    assert_eq!(placed[index].replace(position), None);
+                }
+                StepEvent::Remove { index, position } => {
+                    assert_eq!(placed[index].take(), Some(position));
+                }
+                StepEvent::Prune { index, position } => assert_eq!(placed[index], Some(position)),
+                StepEvent::NeedNewMap { wasted, wastable, .. } => assert!(wasted > wastable),
+            }
+            StepControl::Proceed
+        };
+        let (map, _stats) =
+            watch_best_fit(&tetriminos, &SearchOptions::default(), &mut hook).unwrap();
+        assert_eq!(map.to_string(), ANSWER_1);
+        let expected: Vec<_> = map.tetriminos().iter().map(|(_, p)| Some(*p)).collect();
+        assert_eq!(placed, expected);
+        assert_eq!(sizes.last(), Some(&map.size()));
+
+        // Skipping every map as soon as a tetrimino is written gives the biggest one.
+        let mut hook = |event, _: &[Position]| match event {
+            StepEvent::Place { .. } => StepControl::NextSize,
+            _ => StepControl::Proceed,
+        };
+        let tetriminos = [Tetrimino::Square];
+        let (map, _stats) =
+            watch_best_fit(&tetriminos, &SearchOptions::default(), &mut hook).unwrap();
+        assert_eq!(map.size(), MAX_SIZE);
+    }
+
+    #[test]
+    fn trace_round_trip() {
+        let tetriminos = parse_tetriminos(VALID_1).unwrap();
+        for format in [TraceFormat::Ndjson, TraceFormat::Binary] {
+            let mut events = Vec::new();
+            let mut writer = TraceWriter::new(Vec::new(), format).unwrap();
+            let mut hook = |event, _: &[Position]| {
+                writer.write(&event).unwrap();
+                events.push(event);
+                StepControl::Proceed
+            };
+            watch_best_fit(&tetriminos, &SearchOptions::default(), &mut hook).unwrap();
+            events.push(StepEvent::NeedNewMap { index: 3, wasted: 300, wastable: 252 });
+            writer.write(events.last().unwrap()).unwrap();
+
+            let trace = writer.finish().unwrap();
+            let reader = TraceReader::new(trace.as_slice()).unwrap();
+            assert_eq!(reader.format(), format);
+            let read: Vec<_> = reader.map(Result::unwrap).collect();
+            assert_eq!(read, events);
+        }
+
+        let truncated = [TraceFormat::MAGIC, &[1, 0, 2][..]].concat();
+        let mut reader = TraceReader::new(truncated.as_slice()).unwrap();
+        assert!(reader.next().unwrap().is_err());
+    }
+
+    /// Checks that every tetrimino is drawn with its own shape in a map of the expected size.
+    fn assert_packing(tetriminos: &[Tetrimino], map: &str, size: usize) {
+        let lines: Vec<_> = map.lines().map(str::as_bytes).collect();
+        assert_eq!(lines.len(), size);
+        for (t, c) in tetriminos.iter().zip(b'A'..) {
+            let tiles: Vec<_> = (0..size)
+                .flat_map(|row| (0..size).map(move |col| Position { col, row }))
+                .filter(|p| lines[p.row][p.col] == c)
+                .collect();
+            let top = tiles.iter().map(|p| p.row).min().unwrap();
+            let left = tiles.iter().map(|p| p.col).min().unwrap();
+            let mut buffer = [[false; 4]; 4];
+            tiles.iter().for_each(|p| buffer[p.row - top][p.col - left] = true);
+            assert_eq!(buffer, t.boolean_map(), "tetrimino {}", c as char);
+        }
+    }
+
+    #[test]
+    fn minimal_maps() {
+        for order in [SearchOrder::AnyMinimal, SearchOrder::MostConstrainedCell] {
+            let options = SearchOptions { order, ..SearchOptions::default() };
+            for (name, tetriminos, answer) in answered() {
+                eprintln!("testing {} with {:?}", name, order);
+                if let Some(map) = solve_within(&name, &tetriminos, &options) {
+                    assert_packing(&tetriminos, &map.to_string(), answer.lines().count());
+                }
+            }
+        }
+    }
+
+    /// The indices of the tiles a tetrimino covers at this position of a map,
+    /// row by row, or `None` if it does not fit in the map.
+    fn placement_tiles(
+        tetrimino: Tetrimino,
+        size: usize,
+        row: usize,
+        col: usize,
+    ) -> Option<Vec<usize>> {
+        let tiles = tetrimino.piece().tiles();
+        let inside = |t: &Position| t.row + row < size && t.col + col < size;
+        tiles.iter().map(|t| inside(t).then(|| (t.row + row) * size + t.col + col)).collect()
+    }
+
+    /// Writes the tetriminos in order at every free position, until `found` returns `true`.
+    fn brute_force_place(
+        tetriminos: &[Tetrimino],
+        size: usize,
+        map: &mut [bool],
+        solution: &mut Vec<Position>,
+        found: &mut dyn FnMut(&[Position]) -> bool,
+    ) -> bool {
+        let tetrimino = match tetriminos.get(solution.len()) {
+            Some(tetrimino) => tetrimino,
+            None => return found(solution),
+        };
+
+        for row in 0..size {
+            for col in 0..size {
+                let tiles = match placement_tiles(*tetrimino, size, row, col) {
+                    Some(tiles) if tiles.iter().all(|&t| !map[t]) => tiles,
+                    _ => continue,
+                };
+
+                tiles.iter().for_each(|&t| map[t] = true);
+                solution.push(Position { col, row });
+                if brute_force_place(tetriminos, size, map, solution, found) {
+                    return true;
+                }
+                solution.pop();
+                tiles.iter().for_each(|&t| map[t] = false);
+            }
+        }
+
+        false
+    }
+
+    /// Writes the tetriminos in order at the first free positions, trying every position.
+    fn brute_force_at_size(tetriminos: &[Tetrimino], size: usize) -> Option<Vec<Position>> {
+        let mut map = vec![false; size * size];
+        let mut solution = Vec::new();
+        brute_force_place(tetriminos, size, &mut map, &mut solution, &mut |_| true)
+            .then_some(solution)
+    }
+
+    /// The most tetriminos written in a map of this size, trying to leave out every one.
+    fn brute_force_max_packing(tetriminos: &[Tetrimino], size: usize) -> usize {
+        fn pack(tetriminos: &[Tetrimino], size: usize, map: &mut [bool]) -> usize {
+            let (tetrimino, rest) = match tetriminos.split_first() {
+                Some(split) => split,
+                None => return 0,
+            };
+
+            let mut best = pack(rest, size, map);
+            for row in 0..size {
+                for col in 0..size {
+                    let tiles = placement_tiles(*tetrimino, size, row, col);
+                    if let Some(tiles) = tiles.filter(|ts| ts.iter().all(|&t| !map[t])) {
+                        tiles.iter().for_each(|&t| map[t] = true);
+                        best = best.max(1 + pack(rest, size, map));
+                        tiles.iter().for_each(|&t| map[t] = false);
+                    }
+                }
+            }
+            best
+        }
+
+        pack(tetriminos, size, &mut vec![false; size * size])
+    }
+
+    fn brute_force_bin_packing(tetriminos: &[Tetrimino], size: usize) -> usize {
+        fn pack(tetriminos: &[Tetrimino], size: usize, maps: &mut [Vec<bool>]) -> bool {
+            let (tetrimino, rest) = match tetriminos.split_first() {
+                Some(split) => split,
+                None => return true,
+            };
+
+            for m in 0..maps.len() {
+                for row in 0..size {
+                    for col in 0..size {
+                        let tiles = placement_tiles(*tetrimino, size, row, col);
+                        if let Some(tiles) = tiles.filter(|ts| ts.iter().all(|&t| !maps[m][t])) {
+                            tiles.iter().for_each(|&t| maps[m][t] = true);
+                            let found = pack(rest, size, maps);
+                            tiles.iter().for_each(|&t| maps[m][t] = false);
+                            if found {
+                                return true;
+                            }
+                        }
+                    }
+                }
+            }
+            false
+        }
+
+        let area = tetriminos.len() * Tetrimino::TILE_COUNT;
+        (area.div_ceil(size * size)..)
+            .find(|&count| pack(tetriminos, size, &mut vec![vec![false; size * size]; count]))
+            .unwrap()
+    }
+
+    /// The placements of a map, the same tetriminos can be swapped.
+    fn canonical_fit(
+        fit: impl IntoIterator<Item = (Tetrimino, Position)>,
+    ) -> Vec<(usize, Position)> {
+        let mut fit: Vec<_> = fit.into_iter().map(|(t, p)| (t.ordinal(), p)).collect();
+        fit.sort_unstable();
+        fit
+    }
+
+    fn brute_force_best_fit(tetriminos: &[Tetrimino]) -> VisualMap {
+        (1..)
+            .find_map(|size| {
+                let positions = brute_force_at_size(tetriminos, size)?;
+                Some(VisualMap::new(tetriminos.iter().copied().zip(positions).collect(), size))
+            })
+            .unwrap()
+    }
+
+    /// Checks that every tetrimino is inside the map and does not overlap another one.
+    fn assert_legal(map: &VisualMap) {
+        let mut tiles = vec![false; map.size() * map.size()];
+        for (t, pos) in map.tetriminos() {
+            for tile in t.piece().tiles() {
+                let (row, col) = (pos.row + tile.row, pos.col + tile.col);
+                assert!(row < map.size() && col < map.size(), "{:?} is out of the map", t);
+                assert!(!tiles[row * map.size() + col], "{:?} overlaps another tetrimino", t);
+                tiles[row * map.size() + col] = true;
+            }
+        }
+    }
+
+    fn objective(kind: usize, priority: &[usize]) -> Box<dyn Objective> {
+        let priority = PriorityPieces { indices: priority.to_vec() };
+        match kind {
+            0 => Box::new(BoundingHeight),
+            1 => Box::new(FamilyAdjacency),
+            2 => Box::new(TopLeftHoles),
+            3 => Box::new(priority),
+            _ => Box::new(Weighted {
+                objectives: vec![(3, Box::new(BoundingHeight)), (1, Box::new(priority))],
+            }),
+        }
+    }
+
+    fn small_tetriminos() -> impl Strategy<Value = Vec<Tetrimino>> {
+        let tetrimino = (0..Tetrimino::VARIANT_COUNT).prop_map(|i| Tetrimino::VARIANTS[i]);
+        prop::collection::vec(tetrimino, 1..=6)
+    }
+
+    proptest! {
+        #[test]
+        fn lexicographic_answer_is_brute_force(tetriminos in small_tetriminos()) {
+            let expected = brute_force_best_fit(&tetriminos).to_string();
+            for options in [
+                SearchOptions::default(),
+                SearchOptions { flood_fill: true, ..SearchOptions::default() },
+                SearchOptions { unreachable_pruning: false, ..SearchOptions::default() },
+                SearchOptions { cache_memory: Some(64 * 1024), ..SearchOptions::default() },
+            ] {
+                let (map, _stats) = find_best_fit_with(&tetriminos, &options).unwrap();
+                assert_legal(&map);
+                prop_assert_eq!(map.to_string(), expected.clone(), "{:?}", options);
+            }
+        }
+
+        #[test]
+        fn minimal_size_is_brute_force(tetriminos in small_tetriminos()) {
+            let expected = brute_force_best_fit(&tetriminos);
+            for order in [SearchOrder::AnyMinimal, SearchOrder::MostConstrainedCell] {
+                let options = SearchOptions { order, ..SearchOptions::default() };
+                let (map, _stats) = find_best_fit_with(&tetriminos, &options).unwrap();
+                assert_legal(&map);
+                prop_assert_eq!(map.size(), expected.size(), "{:?}", order);
+            }
+        }
+
+        #[test]
+        fn fits_are_brute_force(
+            // The wasted tiles check skipped the fits of these bars under the first row.
+            tetriminos in prop_oneof![
+                1 => Just(vec![Tetrimino::VerticalBar, Tetrimino::HorizontalBar]),
+                9 => small_tetriminos(),
+            ],
+        ) {
+            let expected = brute_force_best_fit(&tetriminos);
+            let size = expected.size();
+            let mut fits = BTreeSet::new();
+            let mut map = vec![false; size * size];
+            brute_force_place(&tetriminos, size, &mut map, &mut Vec::new(), &mut |solution| {
+                fits.insert(canonical_fit(tetriminos.iter().copied().zip(solution.iter().copied())));
+                false
+            });
+
+            let (maps, _stats) = find_fits(&tetriminos, &SearchOptions::default(), usize::MAX).unwrap();
+            prop_assert_eq!(maps[0].to_string(), expected.to_string());
+            let found: BTreeSet<_> =
+                maps.iter().map(|m| canonical_fit(m.tetriminos().iter().copied())).collect();
+            prop_assert_eq!(found.len(), maps.len());
+            prop_assert_eq!(&found, &fits);
+
+            let (maps, _stats) = find_fits(&tetriminos, &SearchOptions::default(), 2).unwrap();
+            prop_assert_eq!(maps.len(), fits.len().min(2));
+        }
+
+        #[test]
+        fn objective_fit_is_brute_force(
+            tetriminos in small_tetriminos(),
+            kind in 0..5usize,
+            priority in prop::collection::vec(0..6usize, 0..3),
+        ) {
+            let objective = objective(kind, &priority);
+            let size = brute_force_best_fit(&tetriminos).size();
+            let mut expected = usize::MAX;
+            let mut map = vec![false; size * size];
+            brute_force_place(&tetriminos, size, &mut map, &mut Vec::new(), &mut |solution| {
+                let fit: Vec<_> = tetriminos.iter().zip(solution).map(|(t, p)| (*t, Some(*p))).collect();
+                expected = expected.min(objective.cost(size, &fit));
+                false
+            });
+
+            let (found, _stats) = find_best_fit_for(&tetriminos, &*objective, &SearchOptions::default()).unwrap();
+            assert_legal(&found.map);
+            prop_assert_eq!(found.map.size(), size);
+            prop_assert_eq!(found.cost, expected);
+            prop_assert!(found.optimal);
+            let fit: Vec<_> = found.map.tetriminos().iter().map(|(t, p)| (*t, Some(*p))).collect();
+            prop_assert_eq!(objective.cost(size, &fit), expected);
+            for (t, (found, _)) in tetriminos.iter().zip(found.map.tetriminos()) {
+                prop_assert_eq!(t, found);
+            }
+        }
+
+        #[test]
+        fn solver_is_find_best_fit(
+            tetriminos in small_tetriminos(),
+            edits in prop::collection::vec((0..3, any::<prop::sample::Index>(), small_tetriminos()), 1..8),
+        ) {
+            let mut solver = Solver::new(SearchOptions::default());
+            tetriminos.iter().for_each(|t| solver.push(*t).unwrap());
+            let mut expected = tetriminos;
+            for (edit, index, tetriminos) in edits {
+                let tetrimino = tetriminos[0];
+                match edit {
+                    0 if expected.len() < 6 => {
+                        solver.push(tetrimino).unwrap();
+                        expected.push(tetrimino);
+                    }
+                    1 if !expected.is_empty() => {
+                        let index = index.index(expected.len());
+                        prop_assert_eq!(solver.remove(index), expected.remove(index));
+                    }
+                    _ if !expected.is_empty() => {
+                        let index = index.index(expected.len());
+                        solver.replace(index, tetrimino);
+                        expected[index] = tetrimino;
+                    }
+                    _ => continue,
+                }
+
+                prop_assert_eq!(solver.tetriminos(), &expected[..]);
+                let expected_map = find_best_fit(&expected);
+                let map = solver.solve().unwrap().0.to_string();
+                prop_assert_eq!(map, expected_map.to_string());
+                prop_assert_eq!(solver.min_size(), expected_map.size());
+            }
+        }
+
+        #[test]
+        fn fixed_sizes_are_brute_force(tetriminos in small_tetriminos()) {
+            let expected = brute_force_best_fit(&tetriminos);
+            let size = expected.size();
+            let lexicographic = solve_at_size(&tetriminos, size).unwrap();
+            prop_assert_eq!(lexicographic.to_string(), expected.to_string());
+
+            for order in [SearchOrder::Lexicographic, SearchOrder::AnyMinimal, SearchOrder::MostConstrainedCell] {
+                let options = SearchOptions { order, ..SearchOptions::default() };
+                let (map, _stats) = solve_at_size_with(&tetriminos, size - 1, &options).unwrap();
+                prop_assert!(map.is_none(), "{:?}", order);
+                let (map, _stats) = solve_at_size_with(&tetriminos, size + 1, &options).unwrap();
+                assert_legal(&map.unwrap());
+
+                let (range, _stats) = feasible_range(&tetriminos, size + 2, &options).unwrap();
+                prop_assert_eq!(range, Some(size..=size + 2), "{:?}", order);
+                let (range, _stats) = feasible_range(&tetriminos, size - 1, &options).unwrap();
+                prop_assert_eq!(range, None, "{:?}", order);
+            }
+        }
+
+        #[test]
+        fn pinned_size_is_brute_force(
+            tetriminos in prop::collection::vec((0..Tetrimino::VARIANT_COUNT).prop_map(|i| Tetrimino::VARIANTS[i]), 1..=4),
+            at in (0..3usize, 0..3usize),
+            edges in prop::collection::vec(prop::option::of(0..4usize), 4),
+        ) {
+            // The first tetrimino is pinned at a position and the other ones to an edge, or not at all.
+            let edge = |e| [Edge::Top, Edge::Bottom, Edge::Left, Edge::Right][e];
+            let mut pins = vec![(0, Pin::At(Position { col: at.0, row: at.1 }))];
+            let edges = edges.iter().enumerate().skip(1).take(tetriminos.len() - 1);
+            pins.extend(edges.filter_map(|(i, e)| Some((i, Pin::Edge(edge((*e)?))))));
+            let follows = |positions: &[Position], size| {
+                pins.iter().all(|(i, pin)| pin.allows(tetriminos[*i], positions[*i], size))
+            };
+
+            let expected = (1..).find(|&size| {
+                let mut map = vec![false; size * size];
+                brute_force_place(&tetriminos, size, &mut map, &mut Vec::new(), &mut |positions| {
+                    follows(positions, size)
+                })
+            });
+
+            let (map, _stats) = find_best_fit_pinned(&tetriminos, &pins, &SearchOptions::default()).unwrap();
+            let map = map.unwrap();
+            assert_legal(&map);
+            let positions: Vec<_> = map.tetriminos().iter().map(|(_, p)| *p).collect();
+            prop_assert!(follows(&positions, map.size()));
+            prop_assert_eq!(Some(map.size()), expected);
+        }
+
+        #[test]
+        fn max_packing_is_brute_force(tetriminos in small_tetriminos(), size in 2..=5usize) {
+            let expected = brute_force_max_packing(&tetriminos, size);
+            let (packing, _stats) = find_max_packing(&tetriminos, size, &SearchOptions::default()).unwrap();
+            assert_legal(&packing.map);
+            prop_assert_eq!(packing.map.size(), size);
+            prop_assert_eq!(packing.map.tetriminos().len(), expected);
+
+            let mut indices = packing.map.indices().to_vec();
+            for (i, (t, _)) in indices.iter().zip(packing.map.tetriminos()) {
+                prop_assert_eq!(tetriminos[*i], *t);
+            }
+            indices.extend(&packing.left_out);
+            indices.sort_unstable();
+            prop_assert_eq!(indices, (0..tetriminos.len()).collect::<Vec<_>>());
+        }
+
+        #[test]
+        fn bin_packing_is_brute_force(
+            // A 3x3 map holds 2 tetriminos at most, 5 or 6 of them need 3 maps or more.
+            (tetriminos, size) in prop_oneof![
+                (small_tetriminos(), 4..=5usize),
+                (prop::collection::vec(prop::sample::select(&Tetrimino::VARIANTS[2..]), 5..=6), Just(3)),
+            ],
+        ) {
+            let expected = brute_force_bin_packing(&tetriminos, size);
+            let (bins, _stats) = find_bin_packing(&tetriminos, size, &SearchOptions::default());
+            let bins = bins.unwrap();
+            prop_assert!(bins.optimal);
+            let maps = bins.maps;
+            prop_assert_eq!(maps.len(), expected);
+
+            let mut indices: Vec<usize> = Vec::new();
+            for map in &maps {
+                assert_legal(map);
+                prop_assert_eq!(map.size(), size);
+                for (i, (t, _)) in map.indices().iter().zip(map.tetriminos()) {
+                    prop_assert_eq!(tetriminos[*i], *t);
+                }
+                indices.extend(map.indices());
+            }
+            indices.sort_unstable();
+            prop_assert_eq!(indices, (0..tetriminos.len()).collect::<Vec<_>>());
+        }
+
+        #[test]
+        fn larger_map_stays_solvable(tetriminos in small_tetriminos()) {
+            let size = brute_force_best_fit(&tetriminos).size();
+            let options = SearchOptions::default();
+            let mut stats = SearchStats::default();
+            let mut deadline = Deadline::after(None);
+            let tetriminos = Tetriminos::from_tetriminos(&tetriminos);
+            let mut fits = |size| {
+                backtrack_at_size(&tetriminos, size, &options, &mut deadline, &mut stats, None, None)
+                    .unwrap()
+            };
+            prop_assert!(fits(size - 1).is_none());
+            for size in size..size + 3 {
+                prop_assert!(fits(size).is_some());
+            }
         }
     }
 }
@@ -1,15 +1,325 @@
 use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs};
 
-use fillit::{find_best_fit, parse_tetriminos};
+use anyhow::{bail, ensure, Context};
+use fillit::{
+    find_best_fit_for, find_best_fit_pinned, find_best_fit_with, find_bin_packing,
+    find_max_packing, solve_at_size_with, BoundingHeight, FamilyAdjacency, Format, Objective,
+    ParseMode, ParseOptions, Pin, PriorityPieces, SearchOptions, SearchOrder, Tetrimino,
+    TopLeftHoles, Weighted, MAX_SIZE,
+};
+
+mod batch;
+#[cfg(feature = "serve")]
+mod serve;
+mod trace;
+#[cfg(feature = "watch")]
+mod watch;
+
+/// The arguments that tell where and how to read the tetriminos.
+#[derive(Default)]
+struct Input {
+    options: ParseOptions,
+    format: Option<Format>,
+    paths: Vec<String>,
+}
+
+impl Input {
+    /// Reads the input argument, returns `false` if it is not one.
+    fn parse_arg(
+        &mut self,
+        arg: &str,
+        args: &mut impl Iterator<Item = String>,
+    ) -> anyhow::Result<bool> {
+        match arg {
+            "--strict" => self.options.mode = ParseMode::Strict,
+            "--empty" => self.options.empty = args.next().context("missing empty char")?.parse()?,
+            "--full" => self.options.full = args.next().context("missing full char")?.parse()?,
+            "--format" => self.format = Some(args.next().context("missing format")?.parse()?),
+            otherwise if otherwise.starts_with("--") => return Ok(false),
+            path => self.paths.push(path.to_string()),
+        }
+        Ok(true)
+    }
+
+    /// Reads the tetriminos from this file or from the standard input, the format
+    /// is the requested one, the one of the file extension or it is detected.
+    fn read(&self, path: Option<&str>) -> anyhow::Result<Vec<Tetrimino>> {
+        let text = match path {
+            Some(path) => fs::read_to_string(path).with_context(|| format!("reading {}", path))?,
+            None => {
+                let mut buffer = String::new();
+                io::stdin().read_to_string(&mut buffer)?;
+                buffer
+            }
+        };
+
+        self.parse(path.map(Path::new), &text).with_context(|| path.unwrap_or("stdin").to_string())
+    }
+
+    /// Parses the text read from this file.
+    fn parse(&self, path: Option<&Path>, text: &str) -> anyhow::Result<Vec<Tetrimino>> {
+        let extension = path.and_then(Path::extension).and_then(|e| e.to_str());
+        let format = self
+            .format
+            .or_else(|| extension.and_then(Format::from_extension))
+            .unwrap_or_else(|| Format::detect(text, &self.options));
+
+        format.parse(text, &self.options)
+    }
+
+    /// The files to read, the standard input is used when there are none.
+    fn paths(&self) -> Vec<Option<&str>> {
+        if self.paths.is_empty() {
+            vec![None]
+        } else {
+            self.paths.iter().map(|p| Some(p.as_str())).collect()
+        }
+    }
+}
 
 fn main() -> anyhow::Result<()> {
-    let mut buffer = String::new();
-    io::stdin().read_to_string(&mut buffer)?;
+    let mut args = env::args().skip(1).peekable();
+    match args.peek().map(String::as_str) {
+        Some("convert") => convert(args.skip(1)),
+        Some("batch") => batch::batch(args.skip(1)),
+        Some("trace-stats") => trace::trace_stats(args.skip(1)),
+        #[cfg(feature = "serve")]
+        Some("serve") => serve::serve(args.skip(1)),
+        #[cfg(feature = "watch")]
+        Some("watch") => watch::watch(args.skip(1)),
+        _ => solve(args),
+    }
+}
+
+/// Reads the search argument, returns `false` if it is not one.
+fn parse_search_arg(
+    options: &mut SearchOptions,
+    arg: &str,
+    args: &mut impl Iterator<Item = String>,
+) -> anyhow::Result<bool> {
+    match arg {
+        "--flood-fill" => options.flood_fill = true,
+        "--no-unreachable-pruning" => options.unreachable_pruning = false,
+        "--order" => options.order = args.next().context("missing search order")?.parse()?,
+        "--cache" => {
+            let mebibytes: usize = args.next().context("missing cache size")?.parse()?;
+            options.cache_memory = Some(mebibytes * 1024 * 1024);
+        }
+        "--timeout" => {
+            let seconds: f64 = args.next().context("missing timeout")?.parse()?;
+            options.timeout = Some(Duration::from_secs_f64(seconds));
+        }
+        _otherwise => return Ok(false),
+    }
+    Ok(true)
+}
+
+fn solve(mut args: impl Iterator<Item = String>) -> anyhow::Result<()> {
+    let mut input = Input::default();
+    let mut options = SearchOptions::default();
+    let mut print_stats = false;
+    let mut blank_output = '.';
+    let mut trace = None;
+    let mut size = None;
+    let mut pins = Vec::new();
+    let mut max_packing = false;
+    let mut bins = false;
+    let mut objectives = Vec::new();
+    let mut priority = Vec::new();
+    while let Some(arg) = args.next() {
+        if input.parse_arg(&arg, &mut args)? || parse_search_arg(&mut options, &arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--blank-output" => {
+                blank_output = args.next().context("missing blank output char")?.parse()?
+            }
+            "--stats" => print_stats = true,
+            "--trace" => trace = Some(PathBuf::from(args.next().context("missing trace file")?)),
+            "--size" => {
+                let s: usize = args.next().context("missing map size")?.parse()?;
+                ensure!(s <= MAX_SIZE, "the map size must be at most {}", MAX_SIZE);
+                size = Some(s);
+            }
+            "--pin" => pins.push(parse_pin(&args.next().context("missing pin")?)?),
+            "--max-packing" => max_packing = true,
+            "--bins" => bins = true,
+            "--objective" => objectives.push(args.next().context("missing objective")?),
+            "--priority" => priority.push(parse_letter(&args.next().context("missing letter")?)?),
+            otherwise => bail!("unknown argument {}", otherwise),
+        }
+    }
+
+    let paths = input.paths();
+    if trace.is_some() && paths.len() > 1 {
+        bail!("only one map can be traced at a time");
+    }
+    if trace.is_some() && options.order != SearchOrder::Lexicographic {
+        bail!("only the lexicographic search can be traced, --order cannot be used");
+    }
+    if [trace.is_some(), size.is_some(), !pins.is_empty()].iter().filter(|b| **b).count() > 1 {
+        bail!("--trace, --size and --pin cannot be used together");
+    }
+    if max_packing && size.is_none() {
+        bail!("--max-packing needs the --size of the map");
+    }
+    if bins && (size.is_none() || max_packing) {
+        bail!("--bins needs the --size of the maps and cannot be used with --max-packing");
+    }
+    if !objectives.is_empty() && (trace.is_some() || size.is_some() || !pins.is_empty()) {
+        bail!("--objective cannot be used with --trace, --size or --pin");
+    }
+    let objective = parse_objectives(&objectives, &priority)?;
+
+    for (i, path) in paths.iter().enumerate() {
+        let name = path.unwrap_or("stdin");
+        let tetriminos = input.read(*path)?;
+        let mut indices = pins.iter().map(|(index, _)| index).chain(&priority);
+        if let Some(index) = indices.find(|index| **index >= tetriminos.len()) {
+            bail!("there is no tetrimino {} in {}", letter(*index), name);
+        }
 
-    let tetriminos = parse_tetriminos(&buffer)?;
-    let map = find_best_fit(&tetriminos);
+        let (maps, stats) = if let Some(size) = size.filter(|_| max_packing) {
+            let (packing, stats) = find_max_packing(&tetriminos, size, &options)?;
+            if !packing.left_out.is_empty() {
+                let letters: Vec<_> = packing.left_out.iter().map(|i| letter(*i)).collect();
+                eprintln!("{} left out: {}", name, letters.into_iter().collect::<String>());
+            }
+            (Some(vec![packing.map]), stats)
+        } else if let Some(size) = size.filter(|_| bins) {
+            let (bins, stats) = find_bin_packing(&tetriminos, size, &options);
+            if bins.as_ref().is_some_and(|bins| !bins.optimal) {
+                eprintln!("{} may fit in fewer maps, the search timed out", name);
+            }
+            (bins.map(|bins| bins.maps), stats)
+        } else if let Some(objective) = &objective {
+            let (found, stats) = find_best_fit_for(&tetriminos, objective, &options)?;
+            if found.optimal {
+                eprintln!("{} cost: {}", name, found.cost);
+            } else {
+                eprintln!("{} cost: {}, the best found before the timeout", name, found.cost);
+            }
+            (Some(vec![found.map]), stats)
+        } else if let Some(trace) = &trace {
+            let (map, stats) = trace::solve_traced(&tetriminos, &options, trace)?;
+            (Some(vec![map]), stats)
+        } else if let Some(size) = size {
+            let (map, stats) = solve_at_size_with(&tetriminos, size, &options)?;
+            (map.map(|map| vec![map]), stats)
+        } else if !pins.is_empty() {
+            let (map, stats) = find_best_fit_pinned(&tetriminos, &pins, &options)?;
+            (map.map(|map| vec![map]), stats)
+        } else {
+            let (map, stats) = find_best_fit_with(&tetriminos, &options)?;
+            (Some(vec![map]), stats)
+        };
+        if print_stats {
+            eprintln!("{:?}", stats);
+        }
 
-    print!("{}", map);
+        let maps = match (maps, size) {
+            (Some(maps), _) => maps,
+            (None, Some(size)) => {
+                bail!("the tetriminos of {} do not fit in a {}x{} map", name, size, size)
+            }
+            (None, None) => bail!("the pins of {} cannot be followed in any map", name),
+        };
+
+        if paths.len() > 1 {
+            if i != 0 {
+                println!();
+            }
+            println!("{}:", path.unwrap_or("stdin"));
+        }
+        for (m, map) in maps.into_iter().enumerate() {
+            if m != 0 {
+                println!();
+            }
+            print!("{}", map.with_empty(blank_output));
+        }
+    }
 
     Ok(())
 }
+
+/// Reads a pin like `A=0,0` or `C=right`, the letter of the tetrimino followed by its pin.
+fn parse_pin(text: &str) -> anyhow::Result<(usize, Pin)> {
+    let (letter, pin) = text.split_once('=').context("a pin looks like A=0,0 or A=right")?;
+    Ok((parse_letter(letter)?, pin.parse()?))
+}
+
+/// Reads the letter of a tetrimino and returns its index in the input.
+fn parse_letter(letter: &str) -> anyhow::Result<usize> {
+    match letter.as_bytes() {
+        [l @ b'A'..=b'Z'] => Ok((l - b'A') as usize),
+        _ => bail!("invalid tetrimino letter {}", letter),
+    }
+}
+
+/// Reads objectives like `height` or `holes=2`, the name of an objective and its weight,
+/// the `priority` objective brings the `--priority` tetriminos close to the top left corner.
+///
+/// The weights must keep the highest cost of every map in a `usize`.
+fn parse_objectives(texts: &[String], priority: &[usize]) -> anyhow::Result<Option<Weighted>> {
+    if texts.is_empty() {
+        ensure!(priority.is_empty(), "--priority needs the priority --objective");
+        return Ok(None);
+    }
+
+    let mut objectives = Vec::new();
+    let mut highest_cost = Some(0usize);
+    for text in texts {
+        let (name, weight): (&str, usize) = match text.split_once('=') {
+            Some((name, weight)) => (name, weight.parse().context("invalid objective weight")?),
+            None => (text.as_str(), 1),
+        };
+        // The objective and its highest cost, in a map of 26 tetriminos of the biggest size.
+        let (objective, highest): (Box<dyn Objective>, usize) = match name {
+            "height" => (Box::new(BoundingHeight), MAX_SIZE),
+            "families" => (Box::new(FamilyAdjacency), 26 * 25 / 2),
+            "holes" => (Box::new(TopLeftHoles), MAX_SIZE.div_ceil(2).pow(2)),
+            "priority" => {
+                ensure!(!priority.is_empty(), "the priority objective needs --priority tetriminos");
+                let objective = PriorityPieces { indices: priority.to_vec() };
+                (Box::new(objective), priority.len() * 2 * (MAX_SIZE - 1))
+            }
+            _ => bail!("unknown objective {}, try height, families, holes or priority", name),
+        };
+        highest_cost = weight.checked_mul(highest).and_then(|c| highest_cost?.checked_add(c));
+        ensure!(highest_cost.is_some(), "the objective weight {} is too big", weight);
+        objectives.push((weight, objective));
+    }
+    Ok(Some(Weighted { objectives }))
+}
+
+/// Rewrites the tetriminos in another format, converting to an answer solves them.
+fn convert(mut args: impl Iterator<Item = String>) -> anyhow::Result<()> {
+    let mut input = Input::default();
+    let mut options = SearchOptions::default();
+    let mut to = None;
+    while let Some(arg) = args.next() {
+        if input.parse_arg(&arg, &mut args)? || parse_search_arg(&mut options, &arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--to" => to = Some(args.next().context("missing output format")?.parse::<Format>()?),
+            otherwise => bail!("unknown argument {}", otherwise),
+        }
+    }
+
+    let to = to.context("missing --to format")?;
+    for path in input.paths() {
+        let tetriminos = input.read(path)?;
+        print!("{}", to.write(&tetriminos, &options)?);
+    }
+
+    Ok(())
+}
+
+/// The letter of the tetrimino of this index in the answers.
+fn letter(index: usize) -> char {
+    (b'A' + index as u8) as char
+}
@@ -0,0 +1,109 @@
+use enum_ordinalize::Ordinalize;
+
+use crate::deadline::Deadline;
+use crate::{Playground, Position, SearchError, SearchStats, Tetrimino};
+
+/// The state of the search used by `find_max_packing`.
+///
+/// Every tetrimino is either written, at every position in order, or left out.
+/// A branch is abandoned as soon as it cannot write more tetriminos than the best
+/// packing found so far, given the tetriminos and the free tiles left.
+struct MaxPacking<'a> {
+    tetriminos: &'a [Tetrimino],
+    pg: Playground,
+    written: usize,
+    // The first position for the next tetrimino of each type, identical tetriminos
+    // are written in order and only the last ones of a type are left out.
+    farthest: [Position; Tetrimino::VARIANT_COUNT],
+    left_out_types: u32,
+    positions: Vec<Option<Position>>,
+    best: Vec<Option<Position>>,
+    best_count: usize,
+    deadline: &'a mut Deadline,
+    stats: &'a mut SearchStats,
+}
+
+pub fn find_max_packing(
+    tetriminos: &[Tetrimino],
+    size: usize,
+    deadline: &mut Deadline,
+    stats: &mut SearchStats,
+) -> Result<Vec<Option<Position>>, SearchError> {
+    let mut search = MaxPacking {
+        tetriminos,
+        pg: Playground::from_size(size),
+        written: 0,
+        farthest: [Position::default(); Tetrimino::VARIANT_COUNT],
+        left_out_types: 0,
+        positions: vec![None; tetriminos.len()],
+        best: vec![None; tetriminos.len()],
+        best_count: 0,
+        deadline,
+        stats,
+    };
+
+    pack(&mut search, 0)?;
+    Ok(search.best)
+}
+
+/// Returns `true` once every tetrimino is written, no packing can be better.
+fn pack(search: &mut MaxPacking, i: usize) -> Result<bool, SearchError> {
+    if search.written > search.best_count {
+        search.best_count = search.written;
+        search.best.clone_from(&search.positions);
+        if search.best_count == search.tetriminos.len() {
+            return Ok(true);
+        }
+    }
+
+    let size = search.pg.size();
+    let free = size * size - search.written * Tetrimino::TILE_COUNT;
+    let left = search.tetriminos.len() - i;
+    if search.written + left.min(free / Tetrimino::TILE_COUNT) <= search.best_count {
+        return Ok(false);
+    }
+
+    let tetrimino = search.tetriminos[i];
+    let ttype = tetrimino.ordinal();
+    let piece = tetrimino.piece();
+    let tsize = tetrimino.size();
+    let saved_farthest = search.farthest[ttype];
+
+    // Once a tetrimino of this type is left out, the following ones are left out too.
+    if search.left_out_types & (1 << ttype) == 0 {
+        let mut pos = saved_farthest;
+        while pos.row + tsize.row <= size {
+            while pos.col + tsize.col <= size {
+                if search.pg.can_write_piece(piece, &pos) {
+                    search.pg.xor_piece(piece, &pos);
+                    search.stats.placements += 1;
+                    if search.deadline.reached(search.stats.placements) {
+                        return Err(SearchError::TimedOut);
+                    }
+
+                    search.farthest[ttype] =
+                        Position { row: pos.row, col: pos.col + tetrimino.jump_columns() };
+                    search.positions[i] = Some(pos);
+                    search.written += 1;
+                    let done = pack(search, i + 1)?;
+                    search.written -= 1;
+                    search.positions[i] = None;
+                    search.pg.xor_piece(piece, &pos);
+                    if done {
+                        return Ok(true);
+                    }
+                }
+                pos.col += 1;
+            }
+            pos.row += 1;
+            pos.col = 0;
+        }
+        search.farthest[ttype] = saved_farthest;
+    }
+
+    let left_out_types = search.left_out_types;
+    search.left_out_types |= 1 << ttype;
+    let done = pack(search, i + 1)?;
+    search.left_out_types = left_out_types;
+    Ok(done)
+}
@@ -0,0 +1,25 @@
+use crate::{Position, Tetrimino};
+
+/// A cost to minimise among the fits in the smallest map, used by `find_best_fit_for`.
+///
+/// The search writes the tetriminos one after the other and asks for the cost of every
+/// partial fit, where the tetriminos not written yet have no position. The cost of a partial
+/// fit must not be more than the cost of any fit it leads to, the branch is abandoned as soon
+/// as it reaches the cost of the best fit found so far.
+pub trait Objective {
+    /// The cost of the tetriminos written in a map of this size, in the input order.
+    fn cost(&self, size: usize, fit: &[(Tetrimino, Option<Position>)]) -> usize;
+
+    /// Whether the identical tetriminos of these indices can swap their positions without
+    /// changing the cost, such tetriminos are only written in one order.
+    fn interchangeable(&self, _a: usize, _b: usize) -> bool {
+        true
+    }
+}
+
+/// The tiles covered by a tetrimino written at this position.
+pub(crate) fn tiles(tetrimino: Tetrimino, position: Position) -> impl Iterator<Item = Position> {
+    let tiles = tetrimino.piece().tiles();
+    IntoIterator::into_iter(tiles)
+        .map(move |t| Position { row: position.row + t.row, col: position.col + t.col })
+}
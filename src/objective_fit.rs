@@ -0,0 +1,12 @@
+use crate::VisualMap;
+
+/// The fit in the smallest map with the lowest cost, found by `find_best_fit_for`.
+#[derive(Debug, Clone)]
+pub struct ObjectiveFit {
+    pub map: VisualMap,
+    /// The cost of the map given by the objective.
+    pub cost: usize,
+    /// Whether no fit costs less, `false` when the timeout was reached first
+    /// and this is the best fit found so far.
+    pub optimal: bool,
+}
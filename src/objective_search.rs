@@ -0,0 +1,118 @@
+use crate::deadline::Deadline;
+use crate::{Objective, Playground, Position, SearchError, SearchStats, Tetrimino};
+
+/// The state of the search used by `find_best_fit_for`.
+///
+/// Every tetrimino is written in order at every position like the backtracking does,
+/// a branch is abandoned as soon as its cost reaches the one of the best fit found so far.
+struct ObjectiveSearch<'a> {
+    objective: &'a dyn Objective,
+    pg: Playground,
+    wastable: usize,
+    // The number of free tiles isolated by the tetriminos written in the playground.
+    isolated: usize,
+    // The previous interchangeable tetrimino of each one, which is written before it.
+    previous: Vec<Option<usize>>,
+    fit: Vec<(Tetrimino, Option<Position>)>,
+    best: Vec<Position>,
+    best_cost: usize,
+    deadline: &'a mut Deadline,
+    stats: &'a mut SearchStats,
+}
+
+/// Returns the positions of the fit in a map of this size with the lowest cost, and its cost,
+/// starting from a fit of the tetriminos that only a fit with a lower cost can replace.
+/// When the deadline is reached, the best fit found so far is returned as not optimal.
+pub fn find_best_fit(
+    tetriminos: &[Tetrimino],
+    objective: &dyn Objective,
+    size: usize,
+    fit: Vec<Position>,
+    deadline: &mut Deadline,
+    stats: &mut SearchStats,
+) -> (Vec<Position>, usize, bool) {
+    let written: Vec<_> = tetriminos.iter().zip(&fit).map(|(t, p)| (*t, Some(*p))).collect();
+    let best_cost = objective.cost(size, &written);
+    let previous = (0..tetriminos.len())
+        .map(|i| {
+            (0..i)
+                .rev()
+                .find(|&j| tetriminos[j] == tetriminos[i] && objective.interchangeable(j, i))
+        })
+        .collect();
+
+    let mut search = ObjectiveSearch {
+        objective,
+        pg: Playground::from_size(size),
+        wastable: size * size - tetriminos.len() * Tetrimino::TILE_COUNT,
+        isolated: 0,
+        previous,
+        fit: tetriminos.iter().map(|t| (*t, None)).collect(),
+        best: fit,
+        best_cost,
+        deadline,
+        stats,
+    };
+
+    let optimal = best_cost == 0 || search_from(&mut search, 0).is_ok();
+    (search.best, search.best_cost, optimal)
+}
+
+/// Returns `true` once a fit without any cost is found, no fit can be better.
+fn search_from(search: &mut ObjectiveSearch, i: usize) -> Result<bool, SearchError> {
+    let size = search.pg.size();
+    if i == search.fit.len() {
+        let cost = search.objective.cost(size, &search.fit);
+        if cost < search.best_cost {
+            search.best_cost = cost;
+            search.best =
+                search.fit.iter().map(|(_, p)| p.expect("every tetrimino is written")).collect();
+        }
+        return Ok(search.best_cost == 0);
+    }
+
+    let tetrimino = search.fit[i].0;
+    let piece = tetrimino.piece();
+    let tsize = tetrimino.size();
+
+    // Interchangeable tetriminos are written in order, each one after the previous one.
+    let mut pos = match search.previous[i].and_then(|j| search.fit[j].1) {
+        Some(p) => Position { row: p.row, col: p.col + tetrimino.jump_columns() },
+        None => Position::default(),
+    };
+    while pos.row + tsize.row <= size {
+        while pos.col + tsize.col <= size {
+            if search.pg.can_write_piece(piece, &pos) {
+                search.pg.xor_piece(piece, &pos);
+                search.stats.placements += 1;
+                if search.deadline.reached(search.stats.placements) {
+                    return Err(SearchError::TimedOut);
+                }
+
+                let isolated = search.isolated;
+                search.isolated += search.pg.isolated_tiles_around(piece, &pos);
+                search.fit[i].1 = Some(pos);
+                let done = if search.isolated > search.wastable {
+                    search.stats.flood_fill_prunes += 1;
+                    false
+                } else if search.objective.cost(size, &search.fit) >= search.best_cost {
+                    search.stats.bound_prunes += 1;
+                    false
+                } else {
+                    search_from(search, i + 1)?
+                };
+                search.fit[i].1 = None;
+                search.isolated = isolated;
+                search.pg.xor_piece(piece, &pos);
+                if done {
+                    return Ok(true);
+                }
+            }
+            pos.col += 1;
+        }
+        pos.row += 1;
+        pos.col = 0;
+    }
+
+    Ok(false)
+}
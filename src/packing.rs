@@ -0,0 +1,10 @@
+use crate::VisualMap;
+
+/// The most tetriminos that fit in a map, found by `find_max_packing`.
+#[derive(Debug, Clone)]
+pub struct Packing {
+    /// The tetriminos written, with the letters of their indices in the input.
+    pub map: VisualMap,
+    /// The indices in the input of the tetriminos that do not fit.
+    pub left_out: Vec<usize>,
+}
@@ -0,0 +1,47 @@
+use std::{error, fmt};
+
+/// The reasons why an input can not be parsed into tetriminos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// A tile is neither the empty nor the full character.
+    InvalidCharacter(char),
+    LineTooLong,
+    LineTooShort,
+    TooManyLines,
+    TooFewLines,
+    /// The full tiles of a tetrimino do not make one of the known shapes.
+    InvalidTetrimino,
+    /// A tetrimino does not have any full tile.
+    EmptyTetrimino,
+    /// A compact line is neither a tetrimino name nor a mask.
+    UnknownPiece,
+    /// The input is not a JSON array of tetriminos.
+    InvalidJson,
+    /// The input does not end with a newline.
+    MissingNewline,
+    NoTetriminos,
+    TooManyTetriminos,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidCharacter(c) => write!(f, "invalid tetrimino character ({:?})", c),
+            ParseError::LineTooLong => f.write_str("tetrimino line length is too long"),
+            ParseError::LineTooShort => f.write_str("tetrimino line length is too short"),
+            ParseError::TooManyLines => f.write_str("tetrimino number of lines is too big"),
+            ParseError::TooFewLines => f.write_str("tetrimino number of lines is too short"),
+            ParseError::InvalidTetrimino => f.write_str("invalid tetrimino"),
+            ParseError::EmptyTetrimino => f.write_str("empty tetrimino"),
+            ParseError::UnknownPiece => f.write_str("unknown tetrimino name or mask"),
+            ParseError::InvalidJson => f.write_str("invalid JSON array of tetriminos"),
+            ParseError::MissingNewline => f.write_str("missing newline at the end of the input"),
+            ParseError::NoTetriminos => f.write_str("no tetriminos"),
+            ParseError::TooManyTetriminos => {
+                write!(f, "too much tetriminos (max is {})", crate::NUMBER_TETRIMINOS)
+            }
+        }
+    }
+}
+
+impl error::Error for ParseError {}
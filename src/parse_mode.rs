@@ -0,0 +1,28 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+
+/// How closely the input must follow the format of the 42 subject.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Exactly four lines of four `.` or `#` per tetrimino, every line ends with a newline
+    /// and tetriminos are separated by a single empty line, like the moulinette expects.
+    Strict,
+
+    /// Lines can end with CRLF, spaces around the tiles are ignored, tetriminos are
+    /// separated by any number of blank lines and the tile characters can be chosen.
+    #[default]
+    Lenient,
+}
+
+impl FromStr for ParseMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<ParseMode> {
+        match s {
+            "strict" => Ok(ParseMode::Strict),
+            "lenient" => Ok(ParseMode::Lenient),
+            otherwise => bail!("unknown parse mode {}", otherwise),
+        }
+    }
+}
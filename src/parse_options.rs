@@ -0,0 +1,20 @@
+use crate::ParseMode;
+
+/// Tunes how `parse_tetriminos_with` reads the input.
+///
+/// The default options reproduce the behavior of `parse_tetriminos`.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// How closely the input must follow the format of the 42 subject.
+    pub mode: ParseMode,
+    /// The character of the empty tiles, `ParseMode::Strict` only accepts `.`.
+    pub empty: char,
+    /// The character of the full tiles, `ParseMode::Strict` only accepts `#`.
+    pub full: char,
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions { mode: ParseMode::default(), empty: '.', full: '#' }
+    }
+}
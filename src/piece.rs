@@ -1,5 +1,7 @@
 use std::fmt;
 
+use crate::{Position, Tetrimino};
+
 #[derive(Copy, Clone, Eq)]
 pub union Piece {
     pub parts: [u16; 4],
@@ -11,6 +13,23 @@ impl Piece {
         Piece { full: 0 }
     }
 
+    /// Returns the tiles of the piece, relative to its top left corner.
+    #[inline]
+    pub fn tiles(&self) -> [Position; Tetrimino::TILE_COUNT] {
+        let mut tiles = [Position::default(); Tetrimino::TILE_COUNT];
+        let mut i = 0;
+        for (row, part) in unsafe { self.parts }.iter().enumerate() {
+            let mut part = *part;
+            while part != 0 && i < tiles.len() {
+                let col = part.leading_zeros() as usize;
+                part &= !(1 << (15 - col));
+                tiles[i] = Position { col, row };
+                i += 1;
+            }
+        }
+        tiles
+    }
+
     #[inline]
     pub fn shift_right(&mut self, shift: usize) {
         unsafe { self.full >>= shift }
@@ -0,0 +1,43 @@
+use std::str::FromStr;
+
+use anyhow::Context;
+
+use crate::{Edge, Position, Tetrimino};
+
+/// Where a tetrimino must be written, read from `row,col` or from the name of an edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pin {
+    /// The top left corner of the tetrimino is at this position.
+    At(Position),
+    /// The tetrimino touches this side of the map.
+    Edge(Edge),
+}
+
+impl Pin {
+    /// Whether the tetrimino written at this position in a map of this size follows the pin.
+    pub fn allows(&self, tetrimino: Tetrimino, position: Position, size: usize) -> bool {
+        let tsize = tetrimino.size();
+        match self {
+            Pin::At(at) => position == *at,
+            Pin::Edge(Edge::Top) => position.row == 0,
+            Pin::Edge(Edge::Bottom) => position.row + tsize.row == size,
+            Pin::Edge(Edge::Left) => position.col == 0,
+            Pin::Edge(Edge::Right) => position.col + tsize.col == size,
+        }
+    }
+}
+
+impl FromStr for Pin {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Pin> {
+        match s.split_once(',') {
+            Some((row, col)) => {
+                let row = row.trim().parse().with_context(|| format!("invalid row {}", row))?;
+                let col = col.trim().parse().with_context(|| format!("invalid column {}", col))?;
+                Ok(Pin::At(Position { col, row }))
+            }
+            None => Ok(Pin::Edge(s.parse()?)),
+        }
+    }
+}
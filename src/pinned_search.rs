@@ -0,0 +1,127 @@
+use crate::deadline::Deadline;
+use crate::{
+    Pin, Playground, Position, Search, SearchError, SearchOptions, SearchStats, Tetrimino,
+    Tetriminos, MAX_SIZE,
+};
+
+/// A pinned tetrimino, its index in the input and the positions its pins allow.
+struct Pinned {
+    index: usize,
+    tetrimino: Tetrimino,
+    positions: Vec<Position>,
+}
+
+/// The state of the search used by `find_best_fit_pinned`.
+///
+/// The pinned tetriminos are written first, at every position their pins allow,
+/// and the other ones are written in order around them by the usual backtracking.
+struct PinnedSearch<'a> {
+    pinned: Vec<Pinned>,
+    // The indices of the tetriminos that are not pinned.
+    free_indices: Vec<usize>,
+    free: Tetriminos,
+    options: &'a SearchOptions,
+    deadline: &'a mut Deadline,
+    stats: &'a mut SearchStats,
+}
+
+pub fn find_best_fit(
+    tetriminos: &[Tetrimino],
+    pins: &[(usize, Pin)],
+    options: &SearchOptions,
+    deadline: &mut Deadline,
+    stats: &mut SearchStats,
+) -> Result<Option<(Vec<Position>, usize)>, SearchError> {
+    let is_pinned = |i: &usize| pins.iter().any(|(p, _)| p == i);
+    let free_indices: Vec<_> = (0..tetriminos.len()).filter(|i| !is_pinned(i)).collect();
+    let free: Vec<_> = free_indices.iter().map(|&i| tetriminos[i]).collect();
+    let mut search = PinnedSearch {
+        pinned: Vec::new(),
+        free_indices,
+        free: Tetriminos::from_tetriminos(&free),
+        options,
+        deadline,
+        stats,
+    };
+
+    let min_size = Playground::from_number_tetriminos(tetriminos.len()).size();
+    for size in min_size..=MAX_SIZE {
+        search.pinned = (0..tetriminos.len())
+            .filter(is_pinned)
+            .map(|index| {
+                let tetrimino = tetriminos[index];
+                let pins: Vec<_> =
+                    pins.iter().filter(|(i, _)| *i == index).map(|(_, p)| *p).collect();
+                let positions = pinned_positions(tetrimino, &pins, size);
+                Pinned { index, tetrimino, positions }
+            })
+            .collect();
+
+        let mut pg = Playground::from_size(size);
+        let mut solution = vec![Position::default(); tetriminos.len()];
+        if write_pinned(&mut search, 0, &mut pg, &mut solution)? {
+            return Ok(Some((solution, size)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// The positions, row by row, where the tetrimino fits in a map of this size and follows the pins.
+fn pinned_positions(tetrimino: Tetrimino, pins: &[Pin], size: usize) -> Vec<Position> {
+    let tsize = tetrimino.size();
+    let positions = (0..size).flat_map(|row| (0..size).map(move |col| Position { col, row }));
+    positions
+        .filter(|p| p.row + tsize.row <= size && p.col + tsize.col <= size)
+        .filter(|p| pins.iter().all(|pin| pin.allows(tetrimino, *p, size)))
+        .collect()
+}
+
+/// Writes the pinned tetriminos from this one, then searches the free ones around them.
+fn write_pinned(
+    search: &mut PinnedSearch,
+    k: usize,
+    pg: &mut Playground,
+    solution: &mut [Position],
+) -> Result<bool, SearchError> {
+    if k == search.pinned.len() {
+        return write_free(search, pg, solution);
+    }
+
+    let piece = search.pinned[k].tetrimino.piece();
+    for p in 0..search.pinned[k].positions.len() {
+        let position = search.pinned[k].positions[p];
+        if pg.can_write_piece(piece, &position) {
+            pg.xor_piece(piece, &position);
+            solution[search.pinned[k].index] = position;
+            let found = write_pinned(search, k + 1, pg, solution)?;
+            pg.xor_piece(piece, &position);
+            if found {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+fn write_free(
+    search: &mut PinnedSearch,
+    pg: &Playground,
+    solution: &mut [Position],
+) -> Result<bool, SearchError> {
+    let free_tiles: usize = pg.lines().iter().map(|l| l.count_zeros() as usize).sum();
+    if free_tiles < search.free.count * Tetrimino::TILE_COUNT {
+        return Ok(false);
+    }
+
+    let (options, deadline, stats) = (search.options, &mut *search.deadline, &mut *search.stats);
+    let mut free_search = Search::new(&search.free, options, pg.clone(), deadline, stats);
+    match free_search.run()? {
+        Some(positions) => {
+            search.free_indices.iter().zip(positions).for_each(|(&i, p)| solution[i] = p);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
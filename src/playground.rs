@@ -2,9 +2,16 @@ use std::fmt;
 
 use crate::{Piece, Position, Tetrimino};
 
+/// The biggest playground that fits in the lines.
+pub const MAX_SIZE: usize = 16;
+
+/// A piece is written on four lines from its row, the lines after
+/// the biggest playground are fences so that it is never out of bounds.
+const LINES: usize = MAX_SIZE + 3;
+
 #[derive(Clone)]
 pub struct Playground {
-    buff: [u16; 16],
+    buff: [u16; LINES],
     size: usize,
 }
 
@@ -19,9 +26,9 @@ impl Playground {
     }
 
     pub fn from_size(size: usize) -> Playground {
-        assert!(size <= 16);
+        assert!(size <= MAX_SIZE);
 
-        let mut sandbox = Playground { buff: [u16::max_value(); 16], size };
+        let mut sandbox = Playground { buff: [u16::MAX; LINES], size };
         sandbox.generate_fences();
         sandbox
     }
@@ -31,11 +38,16 @@ impl Playground {
         self.size
     }
 
+    /// The lines of the playground, a set bit is a tile that is not free.
+    pub fn lines(&self) -> &[u16] {
+        &self.buff[..self.size]
+    }
+
     #[inline(always)]
     pub fn can_write_piece(&self, mut piece: Piece, pos: &Position) -> bool {
         piece.shift_right(pos.col);
         unsafe {
-            (piece.parts[0] & self.buff[pos.row + 0]) == 0
+            (piece.parts[0] & self.buff[pos.row]) == 0
                 && (piece.parts[1] & self.buff[pos.row + 1]) == 0
                 && (piece.parts[2] & self.buff[pos.row + 2]) == 0
                 && (piece.parts[3] & self.buff[pos.row + 3]) == 0
@@ -46,24 +58,169 @@ impl Playground {
     pub fn xor_piece(&mut self, mut piece: Piece, pos: &Position) {
         piece.shift_right(pos.col);
         unsafe {
-            self.buff[pos.row + 0] ^= piece.parts[0];
+            self.buff[pos.row] ^= piece.parts[0];
             self.buff[pos.row + 1] ^= piece.parts[1];
             self.buff[pos.row + 2] ^= piece.parts[2];
             self.buff[pos.row + 3] ^= piece.parts[3];
         }
     }
 
+    /// Returns the number of free tiles that come before this position, row by row.
+    pub fn free_tiles_before(&self, pos: &Position) -> usize {
+        let rows = pos.row.min(self.size);
+        let mut free: usize =
+            self.buff[..rows].iter().map(|line| line.count_zeros() as usize).sum();
+        if rows < self.size {
+            let before = !u16::MAX.checked_shr(pos.col as u32).unwrap_or(0);
+            free += (!self.buff[rows] & before).count_ones() as usize;
+        }
+        free
+    }
+
+    /// Returns the positions where the piece can be written, starting from this position.
+    ///
+    /// The bit of a column is set if the top left corner of the piece can go there.
+    pub fn valid_positions(&self, piece: Piece, from: &Position) -> [u16; 16] {
+        let tiles = piece.tiles();
+        let free = self.buff.map(|line| !line);
+        let mut valid = [0; 16];
+        for (row, v) in valid.iter_mut().enumerate().take(self.size).skip(from.row) {
+            *v = u16::MAX;
+            for tile in &tiles {
+                *v &= free.get(row + tile.row).map_or(0, |line| line << tile.col);
+            }
+        }
+        if let Some(v) = valid.get_mut(from.row) {
+            *v &= u16::MAX.checked_shr(from.col as u32).unwrap_or(0);
+        }
+        valid
+    }
+
+    /// Returns the free tiles that the piece can cover when written
+    /// at this position or at any position after it, row by row.
+    pub fn coverable_tiles(&self, piece: Piece, from: &Position) -> [u16; 16] {
+        let tiles = piece.tiles();
+        let valid = self.valid_positions(piece, from);
+        let mut coverable = [0; 16];
+        for (row, v) in valid.iter().enumerate().filter(|(_, v)| **v != 0) {
+            for tile in &tiles {
+                coverable[row + tile.row] |= v >> tile.col;
+            }
+        }
+        coverable
+    }
+
+    /// Returns the number of tiles that were isolated by the piece written at this position.
+    ///
+    /// Free tiles are grouped into connected regions, a region that is not a multiple of
+    /// the tetrimino tile count will always waste its remainder, this is also true for
+    /// regions that are too small to hold any tetrimino. The regions that touch the piece
+    /// are the ones the region it was written into split into, so the value returned is
+    /// what their remainders add to the remainder of that region. The sum of the values
+    /// returned along a search path is the wasted remainders of all the regions, minus
+    /// the one of the empty playground, and never overestimates.
+    pub fn isolated_tiles_around(&self, mut piece: Piece, pos: &Position) -> usize {
+        piece.shift_right(pos.col);
+        let parts = unsafe { piece.parts };
+
+        // The free tiles that are direct neighbours of the piece.
+        let mut seeds = [0u16; 16];
+        for (r, part) in parts.iter().enumerate() {
+            let row = pos.row + r;
+            if row >= self.size {
+                break;
+            }
+            seeds[row] |= part << 1 | part >> 1;
+            if row > 0 {
+                seeds[row - 1] |= part;
+            }
+            if row + 1 < self.size {
+                seeds[row + 1] |= part;
+            }
+        }
+        seeds.iter_mut().zip(&self.buff).for_each(|(s, line)| *s &= !line);
+
+        let mut isolated = 0;
+        let mut total_area = 0;
+        for row in pos.row.saturating_sub(1)..self.size.min(pos.row + 5) {
+            while seeds[row] != 0 {
+                let mut region = [0; 16];
+                region[row] = 1 << (15 - seeds[row].leading_zeros());
+                let area = self.fill_region(&mut region, row);
+                // A single region around the piece keeps the remainder of the previous one.
+                if total_area == 0 && seeds.iter().zip(&region).all(|(s, r)| s & !r == 0) {
+                    return 0;
+                }
+                isolated += area % Tetrimino::TILE_COUNT;
+                total_area += area;
+                seeds.iter_mut().zip(&region).for_each(|(s, r)| *s &= !r);
+            }
+        }
+
+        // The region the piece was written into was already counted as wasting tiles.
+        isolated - (total_area + Tetrimino::TILE_COUNT) % Tetrimino::TILE_COUNT
+    }
+
+    /// Grows the region from the given row until it covers all the free tiles connected
+    /// to it and returns its area, sweeping down then up the rows until nothing changes.
+    fn fill_region(&self, region: &mut [u16; 16], row: usize) -> usize {
+        let free = |r: usize| !self.buff[r];
+        let spread = |line: u16, r: usize| spread_line(line, free(r));
+
+        region[row] = spread(region[row], row);
+        let (mut first, mut last) = (row, row);
+        loop {
+            let mut changed = false;
+            for r in first.saturating_sub(1).max(1)..self.size.min(last + 2) {
+                let line = spread(region[r] | region[r - 1] & free(r), r);
+                if line != region[r] {
+                    region[r] = line;
+                    last = last.max(r);
+                    changed = true;
+                }
+            }
+            for r in (first.saturating_sub(1)..last).rev() {
+                let line = spread(region[r] | region[r + 1] & free(r), r);
+                if line != region[r] {
+                    region[r] = line;
+                    first = first.min(r);
+                    changed = true;
+                }
+            }
+            if !changed {
+                return region[first..=last].iter().map(|line| line.count_ones() as usize).sum();
+            }
+        }
+    }
+
+    /// Flips a single tile, this is how a tile is marked as left empty.
+    pub fn xor_tile(&mut self, pos: &Position) {
+        self.buff[pos.row] ^= 1 << (15 - pos.col);
+    }
+
     fn generate_fences(&mut self) {
-        self.buff.fill(u16::max_value());
+        self.buff.fill(u16::MAX);
         for line in self.buff.iter_mut().take(self.size) {
-            *line >>= self.size;
+            *line = line.checked_shr(self.size as u32).unwrap_or(0);
         }
     }
 }
 
+/// Grows the tiles of a line to the runs of free tiles they are in, in both directions.
+///
+/// Adding the tiles to the free ones carries through the free tiles on their left,
+/// the free tiles on their right are found the same way on the reversed line.
+fn spread_line(line: u16, free: u16) -> u16 {
+    let left = |line: u16, free: u16| {
+        let (line, free) = (u32::from(line), u32::from(free));
+        (((free + line) ^ free | line) & free) as u16
+    };
+    left(line, free) | left(line.reverse_bits(), free.reverse_bits()).reverse_bits()
+}
+
 impl fmt::Debug for Playground {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for line in &self.buff {
+        for line in &self.buff[..MAX_SIZE] {
             writeln!(f, "{:016b}", line)?;
         }
         Ok(())
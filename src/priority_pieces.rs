@@ -0,0 +1,19 @@
+use crate::{Objective, Position, Tetrimino};
+
+/// Minimises the distance to the top left corner, in rows plus columns,
+/// of the tetriminos of these indices in the input.
+#[derive(Debug, Default, Clone)]
+pub struct PriorityPieces {
+    pub indices: Vec<usize>,
+}
+
+impl Objective for PriorityPieces {
+    fn cost(&self, _size: usize, fit: &[(Tetrimino, Option<Position>)]) -> usize {
+        let positions = self.indices.iter().filter_map(|&i| fit.get(i)?.1);
+        positions.map(|p| p.row + p.col).sum()
+    }
+
+    fn interchangeable(&self, a: usize, b: usize) -> bool {
+        self.indices.contains(&a) == self.indices.contains(&b)
+    }
+}
@@ -0,0 +1,165 @@
+//! The Python module, built with `maturin develop` and imported as `fillit`.
+//!
+//! The searches release the GIL and raise `TimeoutError` when they take longer
+//! than the timeout, the parse errors are raised as `ValueError`.
+
+use std::time::Duration;
+
+use pyo3::exceptions::{PyTimeoutError, PyValueError};
+use pyo3::prelude::*;
+
+use crate::{
+    find_best_fit_with, parse_tetriminos_with, ParseOptions, SearchError, SearchOptions,
+    SearchStats, Tetrimino, VisualMap,
+};
+
+#[pymethods]
+impl Tetrimino {
+    /// Finds a tetrimino by its name, like `L90` or `T`.
+    #[staticmethod]
+    #[pyo3(name = "from_name")]
+    fn py_from_name(name: &str) -> PyResult<Tetrimino> {
+        Tetrimino::from_name(name)
+            .ok_or_else(|| PyValueError::new_err(format!("unknown tetrimino {}", name)))
+    }
+
+    /// The 4x4 map of this tetrimino, row by row.
+    #[pyo3(name = "boolean_map")]
+    fn py_boolean_map(&self) -> [[bool; 4]; 4] {
+        self.boolean_map()
+    }
+
+    /// The number of rows and columns this tetrimino covers.
+    #[pyo3(name = "size")]
+    fn py_size(&self) -> (usize, usize) {
+        let size = self.size();
+        (size.row, size.col)
+    }
+
+    #[pyo3(name = "jump_columns")]
+    fn py_jump_columns(&self) -> usize {
+        self.jump_columns()
+    }
+
+    #[getter(name)]
+    fn py_name(&self) -> &'static str {
+        self.name()
+    }
+
+    fn __str__(&self) -> String {
+        self.to_text('.', '#')
+    }
+}
+
+/// A solved map, the tetriminos are in the input order.
+#[pyclass(frozen, module = "fillit")]
+pub struct Solution {
+    map: VisualMap,
+}
+
+#[pymethods]
+impl Solution {
+    #[getter]
+    fn size(&self) -> usize {
+        self.map.size()
+    }
+
+    /// The letter, tetrimino, row and column of every tetrimino.
+    #[getter]
+    fn placements(&self) -> Vec<(char, Tetrimino, usize, usize)> {
+        let tetriminos = self.map.tetriminos().iter().zip('A'..);
+        tetriminos.map(|((t, p), letter)| (letter, *t, p.row, p.col)).collect()
+    }
+
+    fn to_json(&self) -> String {
+        self.map.to_json()
+    }
+
+    fn to_svg(&self) -> String {
+        self.map.to_svg()
+    }
+
+    fn __str__(&self) -> String {
+        self.map.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        let size = self.map.size();
+        format!("<Solution {}x{} of {} tetriminos>", size, size, self.map.tetriminos().len())
+    }
+}
+
+#[pymethods]
+impl SearchStats {
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+/// Parses the tetriminos in the 42 subject format, `mode` is `strict` or `lenient`.
+#[pyfunction]
+#[pyo3(signature = (text, mode = "lenient", empty = '.', full = '#'))]
+fn parse_tetriminos(text: &str, mode: &str, empty: char, full: char) -> PyResult<Vec<Tetrimino>> {
+    let mode = mode.parse().map_err(value_error)?;
+    let options = ParseOptions { mode, empty, full };
+    parse_tetriminos_with(text, &options).map_err(value_error)
+}
+
+/// Finds the best fit of the tetriminos, `order` is one of the `--order` of the command line.
+#[pyfunction]
+#[pyo3(signature = (tetriminos, timeout = None, order = "lexicographic", flood_fill = false))]
+fn find_best_fit(
+    py: Python<'_>,
+    tetriminos: Vec<Tetrimino>,
+    timeout: Option<f64>,
+    order: &str,
+    flood_fill: bool,
+) -> PyResult<(Solution, SearchStats)> {
+    let order = order.parse().map_err(value_error)?;
+    let options = SearchOptions { order, flood_fill, ..search_options(timeout)? };
+    let (map, stats) =
+        py.detach(|| find_best_fit_with(&tetriminos, &options)).map_err(search_error)?;
+    Ok((Solution { map }, stats))
+}
+
+/// Finds up to `limit` fits in the smallest map, the lexicographic best fit first.
+#[pyfunction]
+#[pyo3(signature = (tetriminos, limit, timeout = None))]
+fn find_fits(
+    py: Python<'_>,
+    tetriminos: Vec<Tetrimino>,
+    limit: usize,
+    timeout: Option<f64>,
+) -> PyResult<(Vec<Solution>, SearchStats)> {
+    let options = search_options(timeout)?;
+    let (maps, stats) =
+        py.detach(|| crate::find_fits(&tetriminos, &options, limit)).map_err(search_error)?;
+    let solutions = maps.into_iter().map(|map| Solution { map }).collect();
+    Ok((solutions, stats))
+}
+
+fn search_options(timeout: Option<f64>) -> PyResult<SearchOptions> {
+    let timeout = timeout.map(Duration::try_from_secs_f64).transpose().map_err(value_error)?;
+    Ok(SearchOptions { timeout, ..SearchOptions::default() })
+}
+
+fn value_error(error: impl std::fmt::Display) -> PyErr {
+    PyValueError::new_err(format!("{:#}", error))
+}
+
+fn search_error(error: SearchError) -> PyErr {
+    match error {
+        SearchError::TimedOut => PyTimeoutError::new_err(error.to_string()),
+    }
+}
+
+#[pymodule]
+fn fillit(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Tetrimino>()?;
+    m.add_class::<Solution>()?;
+    m.add_class::<SearchStats>()?;
+    m.add_function(wrap_pyfunction!(parse_tetriminos, m)?)?;
+    m.add_function(wrap_pyfunction!(find_best_fit, m)?)?;
+    m.add_function(wrap_pyfunction!(find_fits, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use std::{error, fmt};
+
+/// The reasons why a search stops before finding the best fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchError {
+    /// The search took longer than `SearchOptions::timeout`.
+    TimedOut,
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::TimedOut => f.write_str("the search timed out"),
+        }
+    }
+}
+
+impl error::Error for SearchError {}
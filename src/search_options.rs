@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+use crate::SearchOrder;
+
+/// Tunes how `find_best_fit_with` explores the search tree.
+///
+/// The default options reproduce the behavior of `find_best_fit`.
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    /// The order in which tetriminos are written, and therefore which answer is found.
+    pub order: SearchOrder,
+    /// After each placement, flood-fill the free tiles of the playground
+    /// and prune when the isolated tiles exceed the wastable ones.
+    ///
+    /// It only runs on the placements the unreachable tiles pruning keeps, which already
+    /// prunes most of the same ones, and it is off by default as it is slower on most
+    /// of the `valid_hard_forum_*` inputs.
+    /// `SearchOrder::MostConstrainedCell` already checks every free tile and ignores it.
+    pub flood_fill: bool,
+    /// After each placement, prune when the free tiles that none of the remaining
+    /// tetriminos can cover, each one written after the previous ones of its type,
+    /// exceed the wastable ones. It is on by default.
+    pub unreachable_pruning: bool,
+    /// Remember the search states that lead to no solution, in a table
+    /// that uses at most this amount of bytes, and skip them when reached again.
+    pub cache_memory: Option<usize>,
+    /// Give up with `SearchError::TimedOut` when the search takes longer than this.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> SearchOptions {
+        SearchOptions {
+            order: SearchOrder::default(),
+            flood_fill: false,
+            unreachable_pruning: true,
+            cache_memory: None,
+            timeout: None,
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+use enum_ordinalize::Ordinalize;
+
+use crate::Tetrimino;
+
+/// The order in which the search writes the tetriminos in the playground.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SearchOrder {
+    /// Tetriminos are written in the input order, each one at the first position,
+    /// row by row, where the following ones still fit. This is the answer the
+    /// 42 subject requires, the first tetrimino is as close to the top left as possible.
+    #[default]
+    Lexicographic,
+
+    /// Tetriminos are reordered to write the most constraining ones first, the answer
+    /// is a packing of the smallest size but not necessarily the lexicographic one.
+    AnyMinimal,
+
+    /// The free tile with the fewest tetriminos able to cover it is filled first, or left
+    /// empty when it must be, the answer is a packing of the smallest size.
+    MostConstrainedCell,
+}
+
+impl FromStr for SearchOrder {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<SearchOrder> {
+        match s {
+            "lexicographic" => Ok(SearchOrder::Lexicographic),
+            "any-minimal" => Ok(SearchOrder::AnyMinimal),
+            "most-constrained-cell" => Ok(SearchOrder::MostConstrainedCell),
+            otherwise => bail!("unknown search order {}", otherwise),
+        }
+    }
+}
+
+/// Returns the indices of the tetriminos in the order `SearchOrder::AnyMinimal` writes them.
+pub fn constraint_order(tetriminos: &[Tetrimino]) -> Vec<usize> {
+    let mut order: Vec<_> = (0..tetriminos.len()).collect();
+    order.sort_by_key(|&i| (constraint_rank(tetriminos[i]), tetriminos[i].ordinal()));
+    order
+}
+
+/// The lower the rank, the harder it is to find room for this tetrimino.
+fn constraint_rank(tetrimino: Tetrimino) -> usize {
+    use Tetrimino::*;
+
+    match tetrimino {
+        VerticalBar | HorizontalBar => 0,
+        NormalStairs | NormalStairsRotate90 | MirrorStairs | MirrorStairsRotate90 => 1,
+        Podium | PodiumRotate90 | PodiumRotate180 | PodiumRotate270 => 2,
+        NormalL | NormalLRotate90 | NormalLRotate180 | NormalLRotate270 => 3,
+        MirrorL | MirrorLRotate90 | MirrorLRotate180 | MirrorLRotate270 => 3,
+        Square => 4,
+    }
+}
@@ -0,0 +1,18 @@
+/// Counters collected while searching for the best fit.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "python", pyo3::pyclass(frozen, get_all, module = "fillit"))]
+pub struct SearchStats {
+    /// The number of tetriminos written in a playground.
+    pub placements: u64,
+    /// The number of placements rejected by the flood-fill check.
+    pub flood_fill_prunes: u64,
+    /// The number of placements rejected because the tetriminos left, each one written
+    /// after the previous ones of its type, can no longer cover enough free tiles.
+    pub unreachable_prunes: u64,
+    /// The number of placements rejected because they cost as much as the best fit found so far.
+    pub bound_prunes: u64,
+    /// The number of search states found in the cache of dead states.
+    pub cache_hits: u64,
+    /// The number of search states looked up in the cache but not found.
+    pub cache_misses: u64,
+}
@@ -0,0 +1,330 @@
+use std::io::{self, Read};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context};
+use fillit::{find_best_fit_with, Format, ParseOptions, SearchError, SearchOptions, VisualMap};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::{parse_search_arg, Input};
+
+const DEFAULT_PORT: u16 = 8080;
+
+/// The biggest request body accepted, unless `--max-body` says otherwise.
+const DEFAULT_MAX_BODY: usize = 64 * 1024;
+
+/// Every request is given this time to be solved, unless `--timeout` says otherwise.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The ways a solved map can be sent back.
+#[derive(Clone, Copy)]
+enum Output {
+    Text,
+    Json,
+    Svg,
+}
+
+impl Output {
+    /// Reads the `output` query parameter, or the `Accept` header when there is none.
+    fn from_request(query: Option<&str>, accept: Option<&str>) -> Result<Output, String> {
+        let param = query
+            .into_iter()
+            .flat_map(|q| q.split('&'))
+            .find_map(|pair| pair.strip_prefix("output="));
+
+        match param {
+            Some("text") => Ok(Output::Text),
+            Some("json") => Ok(Output::Json),
+            Some("svg") => Ok(Output::Svg),
+            Some(otherwise) => Err(format!("unknown output {}", otherwise)),
+            None => match accept {
+                Some(a) if a.contains("application/json") => Ok(Output::Json),
+                Some(a) if a.contains("image/svg+xml") => Ok(Output::Svg),
+                _ => Ok(Output::Text),
+            },
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Output::Text => "text/plain; charset=utf-8",
+            Output::Json => "application/json",
+            Output::Svg => "image/svg+xml",
+        }
+    }
+
+    fn write(self, map: &VisualMap) -> String {
+        match self {
+            Output::Text => map.to_string(),
+            Output::Json => map.to_json(),
+            Output::Svg => map.to_svg(),
+        }
+    }
+}
+
+/// What every request is allowed to use.
+struct Limits {
+    max_body: usize,
+    max_requests: usize,
+}
+
+/// Answers the solving requests over HTTP until the process is stopped.
+pub fn serve(mut args: impl Iterator<Item = String>) -> anyhow::Result<()> {
+    let mut input = Input::default();
+    let mut options = SearchOptions { timeout: Some(DEFAULT_TIMEOUT), ..SearchOptions::default() };
+    let mut bind: IpAddr = [127, 0, 0, 1].into();
+    let mut port = DEFAULT_PORT;
+    let mut limits = Limits {
+        max_body: DEFAULT_MAX_BODY,
+        max_requests: thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+    while let Some(arg) = args.next() {
+        if input.parse_arg(&arg, &mut args)? || parse_search_arg(&mut options, &arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--port" => port = args.next().context("missing port")?.parse()?,
+            "--bind" => bind = args.next().context("missing bind address")?.parse()?,
+            "--max-body" => limits.max_body = args.next().context("missing body size")?.parse()?,
+            "--max-requests" => {
+                limits.max_requests = args.next().context("missing number of requests")?.parse()?
+            }
+            otherwise => bail!("unknown argument {}", otherwise),
+        }
+    }
+    if let Some(path) = input.paths.first() {
+        bail!("unknown argument {}, the maps are sent in the requests", path);
+    }
+
+    let server = Server::http((bind, port)).map_err(|e| anyhow!(e))?;
+    eprintln!("Listening on http://{}", server.server_addr());
+    run(&server, &input.options, &options, &limits);
+    Ok(())
+}
+
+/// Answers the requests of the server, each solving request in its own thread.
+fn run(server: &Server, input: &ParseOptions, options: &SearchOptions, limits: &Limits) {
+    let active = AtomicUsize::new(0);
+    thread::scope(|s| {
+        for request in server.incoming_requests() {
+            // The health checks are answered even when every solver is busy.
+            if request.url() == "/health" {
+                if let Err(e) = handle(request, input, options, limits) {
+                    eprintln!("Error: {}", e);
+                }
+                continue;
+            }
+
+            // The requests over the limit are refused instead of waiting for a solver.
+            if active.fetch_add(1, Ordering::SeqCst) >= limits.max_requests {
+                active.fetch_sub(1, Ordering::SeqCst);
+                let _ = request.respond(text(503, "too many requests are being solved\n"));
+                continue;
+            }
+
+            let active = &active;
+            s.spawn(move || {
+                if let Err(e) = handle(request, input, options, limits) {
+                    eprintln!("Error: {}", e);
+                }
+                active.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    });
+}
+
+fn handle(
+    mut request: Request,
+    input: &ParseOptions,
+    options: &SearchOptions,
+    limits: &Limits,
+) -> io::Result<()> {
+    let url = request.url().to_string();
+    let (path, query) = match url.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (url.as_str(), None),
+    };
+
+    let response = match (request.method(), path) {
+        (Method::Get, "/health") => text(200, "ok\n"),
+        (Method::Post, "/solve") => solve(&mut request, query, input, options, limits),
+        (_, "/health") | (_, "/solve") => text(405, "method not allowed\n"),
+        _ => text(404, "not found\n"),
+    };
+    request.respond(response)
+}
+
+/// Solves the tetriminos of the body, in the format of its content type or the detected one.
+fn solve(
+    request: &mut Request,
+    query: Option<&str>,
+    input: &ParseOptions,
+    options: &SearchOptions,
+    limits: &Limits,
+) -> Response<io::Cursor<Vec<u8>>> {
+    let header = |name: &str| {
+        let header =
+            request.headers().iter().find(|h| h.field.as_str().as_str().eq_ignore_ascii_case(name));
+        header.map(|h| h.value.as_str().to_string())
+    };
+    let (content_type, accept) = (header("Content-Type"), header("Accept"));
+
+    let output = match Output::from_request(query, accept.as_deref()) {
+        Ok(output) => output,
+        Err(e) => return text(400, &format!("{}\n", e)),
+    };
+
+    if request.body_length().is_some_and(|len| len > limits.max_body) {
+        return text(413, "the body is too big\n");
+    }
+    let mut body = String::new();
+    let mut reader = request.as_reader().take(limits.max_body as u64 + 1);
+    if let Err(e) = reader.read_to_string(&mut body) {
+        return text(400, &format!("{}\n", e));
+    }
+    if body.len() > limits.max_body {
+        return text(413, "the body is too big\n");
+    }
+
+    let format = match content_type {
+        Some(c) if c.starts_with("application/json") => Format::Json,
+        _ => Format::detect(&body, input),
+    };
+    let tetriminos = match format.parse(&body, input) {
+        Ok(tetriminos) => tetriminos,
+        Err(e) => return text(400, &format!("{:#}\n", e)),
+    };
+
+    match find_best_fit_with(&tetriminos, options) {
+        Ok((map, _stats)) => respond(200, output.content_type(), output.write(&map)),
+        Err(SearchError::TimedOut) => text(504, "the search timed out\n"),
+    }
+}
+
+fn text(status: u16, body: &str) -> Response<io::Cursor<Vec<u8>>> {
+    respond(status, Output::Text.content_type(), body.to_string())
+}
+
+fn respond(status: u16, content_type: &str, body: String) -> Response<io::Cursor<Vec<u8>>> {
+    let header = Header::from_bytes("Content-Type", content_type).expect("valid header");
+    Response::from_string(body).with_status_code(status).with_header(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::net::{SocketAddr, TcpStream};
+    use std::time::Instant;
+
+    use super::*;
+
+    const VALID_1: &str = include_str!("../tests/valid_1.fillit");
+    const VALID_HARD_FORUM: &str = include_str!("../tests/valid_hard_forum.fillit");
+
+    /// Starts a server on an ephemeral port of 127.0.0.1, it lives until the tests end.
+    fn start(options: SearchOptions, limits: Limits) -> SocketAddr {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        thread::spawn(move || run(&server, &ParseOptions::default(), &options, &limits));
+        addr
+    }
+
+    fn default_limits() -> Limits {
+        Limits { max_body: DEFAULT_MAX_BODY, max_requests: 4 }
+    }
+
+    fn send(addr: SocketAddr, method: &str, path: &str, headers: &[&str], body: &str) -> TcpStream {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let mut head =
+            format!("{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n", method, path, addr);
+        head += &format!("Content-Length: {}\r\n", body.len());
+        headers.iter().for_each(|h| head += &format!("{}\r\n", h));
+        stream.write_all(format!("{}\r\n{}", head, body).as_bytes()).unwrap();
+        stream
+    }
+
+    /// Sends a request and returns the status code and the body of the response.
+    fn request(
+        addr: SocketAddr,
+        method: &str,
+        path: &str,
+        headers: &[&str],
+        body: &str,
+    ) -> (u16, String) {
+        let mut response = String::new();
+        send(addr, method, path, headers, body).read_to_string(&mut response).unwrap();
+        let status = response.split(' ').nth(1).unwrap().parse().unwrap();
+        let (_head, body) = response.split_once("\r\n\r\n").unwrap();
+        (status, body.to_string())
+    }
+
+    #[test]
+    fn output_from_request() {
+        let output = |query, accept| Output::from_request(query, accept).map(Output::content_type);
+        assert_eq!(output(None, None), Ok("text/plain; charset=utf-8"));
+        assert_eq!(output(None, Some("application/json")), Ok("application/json"));
+        assert_eq!(output(None, Some("text/html, image/svg+xml")), Ok("image/svg+xml"));
+        assert_eq!(output(Some("output=json"), Some("image/svg+xml")), Ok("application/json"));
+        assert_eq!(output(Some("a=b&output=svg"), None), Ok("image/svg+xml"));
+        assert_eq!(
+            output(Some("output=text"), Some("application/json")),
+            Ok("text/plain; charset=utf-8")
+        );
+        assert_eq!(output(Some("output=png"), None), Err("unknown output png".to_string()));
+    }
+
+    #[test]
+    fn solve_requests() {
+        let limits = Limits { max_body: 1024, ..default_limits() };
+        let addr = start(SearchOptions::default(), limits);
+
+        assert_eq!(request(addr, "GET", "/health", &[], ""), (200, "ok\n".to_string()));
+        assert_eq!(
+            request(addr, "POST", "/solve", &[], VALID_1),
+            (200, "DDAA\nCDDA\nCCCA\nBBBB\n".to_string())
+        );
+        let (status, body) = request(addr, "POST", "/solve?output=json", &[], VALID_1);
+        assert_eq!((status, body.starts_with('{')), (200, true));
+        let (status, body) = request(addr, "POST", "/solve", &["Accept: image/svg+xml"], VALID_1);
+        assert_eq!((status, body.starts_with("<svg")), (200, true));
+
+        assert_eq!(request(addr, "GET", "/solve", &[], "").0, 405);
+        assert_eq!(request(addr, "POST", "/health", &[], "").0, 405);
+        assert_eq!(request(addr, "GET", "/nowhere", &[], "").0, 404);
+        assert_eq!(request(addr, "POST", "/solve?output=png", &[], VALID_1).0, 400);
+        assert_eq!(request(addr, "POST", "/solve", &[], "not tetriminos\n").0, 400);
+        assert_eq!(
+            request(addr, "POST", "/solve", &["Content-Type: application/json"], VALID_1).0,
+            400
+        );
+        assert_eq!(request(addr, "POST", "/solve", &[], &VALID_1.repeat(20)).0, 413);
+    }
+
+    #[test]
+    fn timed_out_request() {
+        let options =
+            SearchOptions { timeout: Some(Duration::from_millis(10)), ..SearchOptions::default() };
+        let addr = start(options, default_limits());
+        assert_eq!(
+            request(addr, "POST", "/solve", &[], VALID_HARD_FORUM),
+            (504, "the search timed out\n".to_string())
+        );
+    }
+
+    #[test]
+    fn health_when_busy() {
+        let options =
+            SearchOptions { timeout: Some(Duration::from_secs(5)), ..SearchOptions::default() };
+        let addr = start(options, Limits { max_requests: 1, ..default_limits() });
+
+        // The hard map keeps the only solver busy, until the easy ones are refused.
+        let _busy = send(addr, "POST", "/solve", &[], VALID_HARD_FORUM);
+        let start = Instant::now();
+        while request(addr, "POST", "/solve", &[], VALID_1).0 != 503 {
+            assert!(start.elapsed() < Duration::from_secs(4), "the solver never got busy");
+        }
+        assert_eq!(request(addr, "GET", "/health", &[], ""), (200, "ok\n".to_string()));
+    }
+}
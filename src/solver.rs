@@ -0,0 +1,97 @@
+use anyhow::ensure;
+
+use crate::{
+    find_best_fit_from, ParseError, SearchError, SearchOptions, SearchStats, Tetrimino, VisualMap,
+    NUMBER_TETRIMINOS,
+};
+
+/// Finds the best fit of tetriminos edited one at a time.
+///
+/// The sizes of the previous solutions bound the next one: adding a tetrimino never makes
+/// the map smaller and removing one shrinks it by two at most, as any tetrimino fits in
+/// two more lines and columns.
+#[derive(Debug, Default, Clone)]
+pub struct Solver {
+    options: SearchOptions,
+    tetriminos: Vec<Tetrimino>,
+    // No map smaller than this can fit the tetriminos.
+    min_size: usize,
+    // The best fit of the current tetriminos, along with the stats of its search.
+    solution: Option<(VisualMap, SearchStats)>,
+}
+
+impl Solver {
+    pub fn new(options: SearchOptions) -> Solver {
+        Solver { options, ..Solver::default() }
+    }
+
+    pub fn options(&self) -> &SearchOptions {
+        &self.options
+    }
+
+    /// Changes the options of the next searches, the solution found with the previous ones is
+    /// forgotten. The smallest map size is the same whatever the options, it is kept.
+    pub fn set_options(&mut self, options: SearchOptions) {
+        self.options = options;
+        self.solution = None;
+    }
+
+    pub fn tetriminos(&self) -> &[Tetrimino] {
+        &self.tetriminos
+    }
+
+    /// The smallest map size that may fit the tetriminos, the size of the solution once solved.
+    pub fn min_size(&self) -> usize {
+        self.min_size
+    }
+
+    /// The best fit of the current tetriminos if it has been found since the last change.
+    pub fn solution(&self) -> Option<(&VisualMap, SearchStats)> {
+        self.solution.as_ref().map(|(map, stats)| (map, *stats))
+    }
+
+    /// Adds a tetrimino after the other ones, fails if there are already 26 of them.
+    pub fn push(&mut self, tetrimino: Tetrimino) -> anyhow::Result<()> {
+        ensure!(self.tetriminos.len() < NUMBER_TETRIMINOS, ParseError::TooManyTetriminos);
+        self.tetriminos.push(tetrimino);
+        self.solution = None;
+        Ok(())
+    }
+
+    /// Removes the tetrimino at this index and returns it, the following ones are shifted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Tetrimino {
+        let tetrimino = self.tetriminos.remove(index);
+        self.min_size =
+            if self.tetriminos.is_empty() { 0 } else { self.min_size.saturating_sub(2) };
+        self.solution = None;
+        tetrimino
+    }
+
+    /// Replaces the tetrimino at this index and returns the previous one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn replace(&mut self, index: usize, tetrimino: Tetrimino) -> Tetrimino {
+        let previous = std::mem::replace(&mut self.tetriminos[index], tetrimino);
+        if previous != tetrimino {
+            self.min_size = self.min_size.saturating_sub(2);
+            self.solution = None;
+        }
+        previous
+    }
+
+    /// Finds the best fit of the current tetriminos, or returns the one already found.
+    pub fn solve(&mut self) -> Result<(&VisualMap, SearchStats), SearchError> {
+        if self.solution.is_none() {
+            let (map, stats) = find_best_fit_from(&self.tetriminos, &self.options, self.min_size)?;
+            self.min_size = map.size();
+            self.solution = Some((map, stats));
+        }
+        Ok(self.solution().expect("just solved"))
+    }
+}
@@ -0,0 +1,9 @@
+/// What the hook of `watch_best_fit` wants the search to do after an event.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StepControl {
+    /// The search goes on.
+    #[default]
+    Proceed,
+    /// The search gives up the current map and tries the next size.
+    NextSize,
+}
@@ -0,0 +1,18 @@
+use crate::Position;
+
+/// What the backtracking search just did, given to the hook of `watch_best_fit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepEvent {
+    /// The search starts over in an empty map of this size.
+    NewMap { size: usize },
+    /// The tetrimino of this index was written at this position.
+    Place { index: usize, position: Position },
+    /// The tetrimino of this index was written but the free tiles left cannot all
+    /// be covered, it will be removed without going deeper.
+    Prune { index: usize, position: Position },
+    /// The tetrimino of this index was removed from this position.
+    Remove { index: usize, position: Position },
+    /// More tiles are wasted before the farthest positions than the map can afford,
+    /// no solution exists at this size.
+    NeedNewMap { index: usize, wasted: usize, wastable: usize },
+}
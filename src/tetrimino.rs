@@ -1,13 +1,14 @@
 use std::str;
 
-use anyhow::{anyhow, ensure, Context};
+use anyhow::ensure;
 use enum_ordinalize::Ordinalize;
 use Tetrimino::*;
 
 use crate::boolean_maps::*;
-use crate::{Piece, Position};
+use crate::{ParseError, Piece, Position};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Ordinalize)]
+#[cfg_attr(feature = "python", pyo3::pyclass(eq, eq_int, frozen, hash, module = "fillit"))]
 #[repr(usize)]
 pub enum Tetrimino {
     /// ```text
@@ -166,24 +167,32 @@ pub enum Tetrimino {
 impl Tetrimino {
     pub const TILE_COUNT: usize = 4;
 
+    /// Reads the four lines of a tetrimino, the spaces around the tiles are ignored
+    /// unless they are the characters of the tiles.
     pub fn from_text(text: &str, empty: char, full: char) -> anyhow::Result<Tetrimino> {
         let mut buffer = [[false; 4]; 4];
-        let mut last_y = 0;
-        let mut last_x = 0;
+        let mut lines = 0;
 
-        for (y, line) in text.lines().map(str::trim).enumerate() {
-            last_y = y;
+        let is_padding = |c: char| c.is_whitespace() && c != empty && c != full;
+        for (y, line) in text.lines().map(|l| l.trim_matches(is_padding)).enumerate() {
+            ensure!(y < 4, ParseError::TooManyLines);
+            let mut length = 0;
             for (x, c) in line.chars().enumerate() {
-                last_x = x;
-                ensure!(c == full || c == empty, "invalid tetrimino character ({})", c);
-                ensure!(x < 4, "tetrimino line length is too long");
-                ensure!(y < 4, "tetrimino number of lines is too big");
+                ensure!(c == full || c == empty, ParseError::InvalidCharacter(c));
+                ensure!(x < 4, ParseError::LineTooLong);
                 buffer[y][x] = c == full;
+                length = x + 1;
             }
-            ensure!(last_x == 3, "tetrimino line length is too short");
+            ensure!(length == 4, ParseError::LineTooShort);
+            lines = y + 1;
         }
-        ensure!(last_y == 3, "tetrimino number of lines is too short");
+        ensure!(lines == 4, ParseError::TooFewLines);
 
+        Tetrimino::from_boolean_map(buffer)
+    }
+
+    /// Finds the tetrimino drawn anywhere in this 4x4 map, not necessarily in the top left.
+    pub fn from_boolean_map(mut buffer: [[bool; 4]; 4]) -> anyhow::Result<Tetrimino> {
         let left_full = buffer.iter().flat_map(|b| b.iter().position(|c| *c)).min();
         let top_full = buffer.iter().position(|b| b.iter().any(|c| *c));
 
@@ -191,10 +200,44 @@ impl Tetrimino {
             Some((y, x)) => {
                 buffer.rotate_left(y);
                 buffer.iter_mut().for_each(|b| b.rotate_left(x));
-                Tetrimino::from_buffer_4x4(buffer).context("invalid tetrimino")
+                Tetrimino::from_buffer_4x4(buffer)
+                    .ok_or_else(|| ParseError::InvalidTetrimino.into())
             }
-            None => Err(anyhow!("empty tetrimino")),
+            None => Err(ParseError::EmptyTetrimino.into()),
+        }
+    }
+
+    /// Finds the tetrimino drawn in this mask of a 4x4 map,
+    /// row by row, the highest bit is the top left tile.
+    pub fn from_mask(mask: u16) -> anyhow::Result<Tetrimino> {
+        let mut buffer = [[false; 4]; 4];
+        for (i, tile) in buffer.iter_mut().flatten().enumerate() {
+            *tile = mask & (1 << (15 - i)) != 0;
+        }
+        Tetrimino::from_boolean_map(buffer)
+    }
+
+    /// Finds the tetrimino with this name, as returned by `Tetrimino::name`.
+    pub fn from_name(name: &str) -> Option<Tetrimino> {
+        Tetrimino::VARIANTS.iter().copied().find(|t| t.name() == name)
+    }
+
+    /// Reads exactly four lines of four `.` or `#`, separated by newlines, nothing is trimmed.
+    pub fn from_strict_text(text: &str) -> anyhow::Result<Tetrimino> {
+        if let Some(c) = text.chars().find(|c| !matches!(c, '.' | '#' | '\n')) {
+            return Err(ParseError::InvalidCharacter(c).into());
+        }
+
+        let lines: Vec<_> = text.split('\n').collect();
+        ensure!(lines.len() <= 4, ParseError::TooManyLines);
+        ensure!(lines.len() == 4, ParseError::TooFewLines);
+
+        for line in lines {
+            ensure!(line.len() <= 4, ParseError::LineTooLong);
+            ensure!(line.len() == 4, ParseError::LineTooShort);
         }
+
+        Tetrimino::from_text(text, '.', '#')
     }
 
     fn from_buffer_4x4(buffer: [[bool; 4]; 4]) -> Option<Tetrimino> {
@@ -246,6 +289,53 @@ impl Tetrimino {
         }
     }
 
+    /// The mask of the 4x4 map of this tetrimino, row by row, the highest bit is the top left tile.
+    pub fn mask(&self) -> u16 {
+        let tiles = self.boolean_map();
+        tiles.iter().flatten().fold(0, |mask, full| mask << 1 | *full as u16)
+    }
+
+    /// The usual name of this tetrimino shape followed by its counter clockwise rotation,
+    /// a `J` is a mirrored `L`, a `S` and a `Z` are stairs and a `T` is a podium.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            VerticalBar => "I",
+            HorizontalBar => "I90",
+            Square => "O",
+            NormalL => "L",
+            NormalLRotate90 => "L90",
+            NormalLRotate180 => "L180",
+            NormalLRotate270 => "L270",
+            MirrorL => "J",
+            MirrorLRotate90 => "J90",
+            MirrorLRotate180 => "J180",
+            MirrorLRotate270 => "J270",
+            NormalStairs => "S",
+            NormalStairsRotate90 => "S90",
+            MirrorStairs => "Z",
+            MirrorStairsRotate90 => "Z90",
+            Podium => "T",
+            PodiumRotate90 => "T90",
+            PodiumRotate180 => "T180",
+            PodiumRotate270 => "T270",
+        }
+    }
+
+    /// The name of this tetrimino shape whatever its rotation, like `L` for an `L90`.
+    pub fn family(&self) -> &'static str {
+        &self.name()[..1]
+    }
+
+    /// Draws the 4x4 map of this tetrimino, every line ends with a newline.
+    pub fn to_text(&self, empty: char, full: char) -> String {
+        let mut text = String::with_capacity(20);
+        for line in &self.boolean_map() {
+            text.extend(line.iter().map(|b| if *b { full } else { empty }));
+            text.push('\n');
+        }
+        text
+    }
+
     pub const fn piece(&self) -> Piece {
         const fn create_part(bs: [bool; 4]) -> u16 {
             (bs[0] as u16) << 15
@@ -9,6 +9,8 @@ pub struct Tetriminos {
     pub pieces: [Piece; NUMBER_TETRIMINOS],
     pub is_first_occurence: [bool; NUMBER_TETRIMINOS],
     pub is_last_piece_type: [bool; NUMBER_TETRIMINOS],
+    /// The types of the tetriminos from this one to the last one, as a bitset.
+    pub remaining_types: [u32; NUMBER_TETRIMINOS],
     pub count: usize,
 }
 
@@ -20,6 +22,7 @@ impl Tetriminos {
         let mut jump_columns = [0; NUMBER_TETRIMINOS];
         let mut is_first_occurence = [false; NUMBER_TETRIMINOS];
         let mut is_last_piece_type = [false; NUMBER_TETRIMINOS];
+        let mut remaining_types = [0; NUMBER_TETRIMINOS];
 
         pieces.iter_mut().zip(tetriminos).for_each(|(p, tet)| *p = tet.piece());
         types.iter_mut().zip(tetriminos).for_each(|(t, tet)| *t = tet.ordinal());
@@ -36,6 +39,12 @@ impl Tetriminos {
             is_last_piece_type[idx] = true;
         }
 
+        let mut types_mask = 0;
+        for (rt, t) in remaining_types.iter_mut().zip(&types).take(tetriminos.len()).rev() {
+            types_mask |= 1 << t;
+            *rt = types_mask;
+        }
+
         Tetriminos {
             types,
             jump_columns,
@@ -43,6 +52,7 @@ impl Tetriminos {
             pieces,
             is_first_occurence,
             is_last_piece_type,
+            remaining_types,
             count: tetriminos.len(),
         }
     }
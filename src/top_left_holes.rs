@@ -0,0 +1,19 @@
+use crate::objective::tiles;
+use crate::{Objective, Position, Tetrimino};
+
+/// Minimises the number of free tiles left in the top left quarter of the map,
+/// the square of half the size of the map rounded up.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TopLeftHoles;
+
+impl Objective for TopLeftHoles {
+    fn cost(&self, size: usize, fit: &[(Tetrimino, Option<Position>)]) -> usize {
+        let side = size.div_ceil(2);
+        let written = fit.iter().filter_map(|(t, p)| Some(tiles(*t, (*p)?)));
+        let covered = written.flatten().filter(|t| t.row < side && t.col < side).count();
+
+        // The tetriminos not written yet may still cover some of the free tiles.
+        let coverable = fit.iter().filter(|(_, p)| p.is_none()).count() * Tetrimino::TILE_COUNT;
+        (side * side - covered).saturating_sub(coverable)
+    }
+}
@@ -0,0 +1,146 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use fillit::{
+    watch_best_fit, Position, SearchOptions, SearchStats, StepControl, StepEvent, Tetrimino,
+    TraceFormat, TraceReader, TraceWriter, VisualMap,
+};
+
+use crate::letter;
+
+/// Solves the tetriminos and writes every step of the search in the trace file, in the
+/// format of its extension. The trace is kept when the search times out.
+pub fn solve_traced(
+    tetriminos: &[Tetrimino],
+    options: &SearchOptions,
+    path: &Path,
+) -> anyhow::Result<(VisualMap, SearchStats)> {
+    let extension = path.extension().and_then(|e| e.to_str());
+    let format = extension.and_then(TraceFormat::from_extension).unwrap_or_default();
+    let file = File::create(path).with_context(|| format!("creating {}", path.display()))?;
+    let mut writer = TraceWriter::new(BufWriter::new(file), format)?;
+
+    let mut error = None;
+    let mut hook = |event, _: &[Position]| {
+        if error.is_none() {
+            error = writer.write(&event).err();
+        }
+        StepControl::Proceed
+    };
+    let result = watch_best_fit(tetriminos, options, &mut hook);
+
+    if let Some(e) = error {
+        return Err(e).with_context(|| format!("writing {}", path.display()));
+    }
+    writer.finish().with_context(|| format!("writing {}", path.display()))?;
+    Ok(result?)
+}
+
+/// What happened at a depth of the search, the index of the tetrimino being written.
+#[derive(Default)]
+struct Depth {
+    placements: u64,
+    prunes: u64,
+    // The times no tetrimino of this index could be written after the previous one.
+    dead_ends: u64,
+    need_new_maps: u64,
+}
+
+/// Summarises a trace written by `fillit --trace`.
+pub fn trace_stats(mut args: impl Iterator<Item = String>) -> anyhow::Result<()> {
+    let path = args.next().context("missing trace file")?;
+    if let Some(arg) = args.next() {
+        bail!("unknown argument {}", arg);
+    }
+
+    let file = File::open(&path).with_context(|| format!("reading {}", path))?;
+    let reader = TraceReader::new(BufReader::new(file))?;
+    let format = reader.format();
+
+    let mut depths: Vec<Depth> = Vec::new();
+    let mut pruned = Vec::new();
+    let (mut maps, mut events) = (0u64, 0u64);
+    for event in reader {
+        let event = event.with_context(|| format!("{} event {}", path, events))?;
+        events += 1;
+
+        let index = match event {
+            StepEvent::NewMap { .. } => 0,
+            StepEvent::Place { index, .. }
+            | StepEvent::Prune { index, .. }
+            | StepEvent::Remove { index, .. }
+            | StepEvent::NeedNewMap { index, .. } => index + 1,
+        };
+        if depths.len() <= index {
+            depths.resize_with(index + 1, Depth::default);
+            pruned.resize(index + 1, false);
+        }
+
+        match event {
+            StepEvent::NewMap { .. } => maps += 1,
+            StepEvent::Place { index, .. } => {
+                depths[index].placements += 1;
+                pruned[index] = false;
+            }
+            StepEvent::Prune { index, .. } => {
+                depths[index].prunes += 1;
+                pruned[index] = true;
+            }
+            // A removed tetrimino that was not pruned went deeper and came back,
+            // the next tetrimino could not be written anywhere.
+            StepEvent::Remove { index, .. } if !pruned[index] => depths[index + 1].dead_ends += 1,
+            StepEvent::Remove { .. } => (),
+            StepEvent::NeedNewMap { index, .. } => depths[index].need_new_maps += 1,
+        }
+    }
+
+    println!("{} events in {} maps ({:?} trace)", events, maps, format);
+    println!(
+        "{:>5} {:>5} {:>12} {:>9} {:>12} {:>7} {:>12} {:>9}",
+        "depth", "piece", "placements", "branching", "prunes", "pruned", "dead ends", "new maps"
+    );
+
+    // The search goes deeper from the placements that are not pruned.
+    let mut expansions = maps;
+    for (i, depth) in depths.iter().enumerate() {
+        if depth.placements == 0 && depth.dead_ends == 0 && depth.need_new_maps == 0 {
+            continue;
+        }
+        let branching = ratio(depth.placements, expansions);
+        let pruned = 100.0 * ratio(depth.prunes, depth.placements);
+        println!(
+            "{:>5} {:>5} {:>12} {:>9.2} {:>12} {:>6.1}% {:>12} {:>9}",
+            i,
+            letter(i),
+            depth.placements,
+            branching,
+            depth.prunes,
+            pruned,
+            depth.dead_ends,
+            depth.need_new_maps
+        );
+        expansions = depth.placements - depth.prunes;
+    }
+
+    let worst = depths.iter().enumerate().max_by_key(|(_, d)| d.dead_ends);
+    if let Some((i, depth)) = worst.filter(|(_, d)| d.dead_ends != 0) {
+        println!(
+            "tetrimino {} (index {}) causes the most backtracking, {} dead ends",
+            letter(i),
+            i,
+            depth.dead_ends
+        );
+    }
+
+    Ok(())
+}
+
+fn ratio(a: u64, b: u64) -> f64 {
+    if b == 0 {
+        0.0
+    } else {
+        a as f64 / b as f64
+    }
+}
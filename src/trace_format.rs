@@ -0,0 +1,39 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+
+/// The formats a search trace can be written in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// One JSON object per line and per step, easy to read with any tool.
+    Ndjson,
+    /// A few bytes per step, the traces of long searches stay small.
+    #[default]
+    Binary,
+}
+
+impl TraceFormat {
+    /// The header of the binary traces, the traces without it are newline-delimited JSON.
+    pub const MAGIC: &'static [u8] = b"fillit-trace-1\n";
+
+    /// Finds the format associated with this file extension.
+    pub fn from_extension(extension: &str) -> Option<TraceFormat> {
+        match extension {
+            "ndjson" | "jsonl" => Some(TraceFormat::Ndjson),
+            "trace" => Some(TraceFormat::Binary),
+            _otherwise => None,
+        }
+    }
+}
+
+impl FromStr for TraceFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<TraceFormat> {
+        match s {
+            "ndjson" => Ok(TraceFormat::Ndjson),
+            "binary" => Ok(TraceFormat::Binary),
+            otherwise => bail!("unknown trace format {}", otherwise),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::io::{self, BufRead};
+
+use anyhow::{bail, Context};
+use serde_json::Value;
+
+use crate::{Position, StepEvent, TraceFormat};
+
+/// Reads the steps written by a `TraceWriter`, the format is found from the header.
+pub struct TraceReader<R> {
+    input: R,
+    format: TraceFormat,
+    // The first line of a newline-delimited JSON trace, read to look for the header.
+    first_line: Option<Vec<u8>>,
+}
+
+impl<R: BufRead> TraceReader<R> {
+    pub fn new(mut input: R) -> io::Result<TraceReader<R>> {
+        let mut first_line = Vec::new();
+        input.read_until(b'\n', &mut first_line)?;
+        if first_line == TraceFormat::MAGIC {
+            Ok(TraceReader { input, format: TraceFormat::Binary, first_line: None })
+        } else {
+            Ok(TraceReader { input, format: TraceFormat::Ndjson, first_line: Some(first_line) })
+        }
+    }
+
+    pub fn format(&self) -> TraceFormat {
+        self.format
+    }
+
+    fn read_json(&mut self) -> anyhow::Result<Option<StepEvent>> {
+        let mut line = self.first_line.take().unwrap_or_default();
+        while line.iter().all(u8::is_ascii_whitespace) {
+            line.clear();
+            if self.input.read_until(b'\n', &mut line)? == 0 {
+                return Ok(None);
+            }
+        }
+
+        let value: Value = serde_json::from_slice(&line)?;
+        let field = |name: &str| -> anyhow::Result<usize> {
+            let n = value.get(name).and_then(Value::as_u64);
+            Ok(n.with_context(|| format!("missing {} field", name))? as usize)
+        };
+        let position = || -> anyhow::Result<Position> {
+            Ok(Position { row: field("row")?, col: field("col")? })
+        };
+
+        let event = match value.get("event").and_then(Value::as_str) {
+            Some("new-map") => StepEvent::NewMap { size: field("size")? },
+            Some("place") => StepEvent::Place { index: field("index")?, position: position()? },
+            Some("prune") => StepEvent::Prune { index: field("index")?, position: position()? },
+            Some("remove") => StepEvent::Remove { index: field("index")?, position: position()? },
+            Some("need-new-map") => StepEvent::NeedNewMap {
+                index: field("index")?,
+                wasted: field("wasted")?,
+                wastable: field("wastable")?,
+            },
+            Some(otherwise) => bail!("unknown event {}", otherwise),
+            None => bail!("missing event field"),
+        };
+        Ok(Some(event))
+    }
+
+    fn read_binary(&mut self) -> anyhow::Result<Option<StepEvent>> {
+        let mut tag = [0u8];
+        if self.input.read(&mut tag)? == 0 {
+            return Ok(None);
+        }
+
+        let mut fields = [0u8; 5];
+        let len = match tag[0] {
+            0 => 1,
+            1..=3 => 3,
+            4 => 5,
+            otherwise => bail!("unknown event tag {}", otherwise),
+        };
+        self.input.read_exact(&mut fields[..len]).context("truncated event")?;
+
+        let [a, b, c, d, e] = fields.map(usize::from);
+        let position = Position { row: b, col: c };
+        let event = match tag[0] {
+            0 => StepEvent::NewMap { size: a },
+            1 => StepEvent::Place { index: a, position },
+            2 => StepEvent::Prune { index: a, position },
+            3 => StepEvent::Remove { index: a, position },
+            _ => StepEvent::NeedNewMap { index: a, wasted: b | c << 8, wastable: d | e << 8 },
+        };
+        Ok(Some(event))
+    }
+}
+
+impl<R: BufRead> Iterator for TraceReader<R> {
+    type Item = anyhow::Result<StepEvent>;
+
+    fn next(&mut self) -> Option<anyhow::Result<StepEvent>> {
+        let event = match self.format {
+            TraceFormat::Ndjson => self.read_json(),
+            TraceFormat::Binary => self.read_binary(),
+        };
+        event.transpose()
+    }
+}
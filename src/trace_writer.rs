@@ -0,0 +1,84 @@
+use std::io::{self, Write};
+
+use serde_json::json;
+
+use crate::{StepEvent, TraceFormat};
+
+/// Writes the steps of a search, for example from the hook of `watch_best_fit`.
+pub struct TraceWriter<W> {
+    out: W,
+    format: TraceFormat,
+}
+
+impl<W: Write> TraceWriter<W> {
+    /// Starts a trace, writing the binary header if needed.
+    pub fn new(mut out: W, format: TraceFormat) -> io::Result<TraceWriter<W>> {
+        if format == TraceFormat::Binary {
+            out.write_all(TraceFormat::MAGIC)?;
+        }
+        Ok(TraceWriter { out, format })
+    }
+
+    pub fn write(&mut self, event: &StepEvent) -> io::Result<()> {
+        match self.format {
+            TraceFormat::Ndjson => {
+                serde_json::to_writer(&mut self.out, &to_json(event))?;
+                self.out.write_all(b"\n")
+            }
+            TraceFormat::Binary => {
+                let mut record = [0u8; 6];
+                let len = encode(event, &mut record);
+                self.out.write_all(&record[..len])
+            }
+        }
+    }
+
+    /// Flushes the trace and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+fn to_json(event: &StepEvent) -> serde_json::Value {
+    match *event {
+        StepEvent::NewMap { size } => json!({ "event": "new-map", "size": size }),
+        StepEvent::Place { index, position } => {
+            json!({ "event": "place", "index": index, "row": position.row, "col": position.col })
+        }
+        StepEvent::Prune { index, position } => {
+            json!({ "event": "prune", "index": index, "row": position.row, "col": position.col })
+        }
+        StepEvent::Remove { index, position } => {
+            json!({ "event": "remove", "index": index, "row": position.row, "col": position.col })
+        }
+        StepEvent::NeedNewMap { index, wasted, wastable } => json!({
+            "event": "need-new-map",
+            "index": index,
+            "wasted": wasted,
+            "wastable": wastable,
+        }),
+    }
+}
+
+/// Writes a tag byte followed by the fields, the sizes, indexes, rows and columns
+/// fit in a byte and the wasted tiles in two little-endian bytes.
+fn encode(event: &StepEvent, record: &mut [u8; 6]) -> usize {
+    let (tag, index, position) = match *event {
+        StepEvent::NewMap { size } => {
+            record[..2].copy_from_slice(&[0, size as u8]);
+            return 2;
+        }
+        StepEvent::Place { index, position } => (1, index, position),
+        StepEvent::Prune { index, position } => (2, index, position),
+        StepEvent::Remove { index, position } => (3, index, position),
+        StepEvent::NeedNewMap { index, wasted, wastable } => {
+            record[..2].copy_from_slice(&[4, index as u8]);
+            record[2..4].copy_from_slice(&(wasted as u16).to_le_bytes());
+            record[4..6].copy_from_slice(&(wastable as u16).to_le_bytes());
+            return 6;
+        }
+    };
+    record[..4].copy_from_slice(&[tag, index as u8, position.row as u8, position.col as u8]);
+    4
+}
@@ -0,0 +1,94 @@
+use std::mem;
+
+use enum_ordinalize::Ordinalize;
+
+use crate::{Position, Tetrimino};
+
+/// A key that identifies a search state: the tiles of the playground,
+/// the index of the next tetrimino to place and, for each type,
+/// what constrains the remaining tetriminos.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct StateKey {
+    lines: [u16; 16],
+    index: u8,
+    constraints: [[u8; 2]; Tetrimino::VARIANT_COUNT],
+}
+
+impl StateKey {
+    pub fn new(lines: &[u16], index: usize, farthest: &[Position], types_mask: u32) -> StateKey {
+        let mut key = StateKey {
+            lines: [0; 16],
+            index: index as u8,
+            constraints: [[0; 2]; Tetrimino::VARIANT_COUNT],
+        };
+        key.lines[..lines.len()].copy_from_slice(lines);
+        for (t, (k, far)) in key.constraints.iter_mut().zip(farthest).enumerate() {
+            if types_mask & (1 << t) != 0 {
+                *k = [far.row as u8, far.col as u8];
+            }
+        }
+        key
+    }
+
+    /// Creates a key for a search that does not write the tetriminos in a fixed order,
+    /// only the number of tetriminos remaining for each type matters.
+    pub fn with_remaining(lines: &[u16], remaining: &[u32]) -> StateKey {
+        let mut key = StateKey {
+            lines: [0; 16],
+            index: u8::MAX,
+            constraints: [[0; 2]; Tetrimino::VARIANT_COUNT],
+        };
+        key.lines[..lines.len()].copy_from_slice(lines);
+        for (k, r) in key.constraints.iter_mut().zip(remaining) {
+            *k = [r.count_ones() as u8, 0];
+        }
+        key
+    }
+
+    fn hash(&self) -> u64 {
+        // A simple multiplicative hash, the keys are dense and well distributed.
+        const K: u64 = 0x517c_c1b7_2722_0a95;
+        let mut hash = self.index as u64;
+        for line in &self.lines {
+            hash = (hash.rotate_left(5) ^ *line as u64).wrapping_mul(K);
+        }
+        for [row, col] in &self.constraints {
+            hash = (hash.rotate_left(5) ^ (*row as u64) << 8 ^ *col as u64).wrapping_mul(K);
+        }
+        hash
+    }
+}
+
+/// A bounded, direct-mapped table that remembers the search states
+/// from which no solution can be found.
+///
+/// When two states fall in the same slot, the newest one replaces the
+/// oldest, the memory used never grows after the table is created.
+pub struct TranspositionTable {
+    slots: Vec<Option<StateKey>>,
+}
+
+impl TranspositionTable {
+    /// Creates a table that uses at most the given amount of bytes.
+    pub fn with_memory(bytes: usize) -> TranspositionTable {
+        let count = (bytes / mem::size_of::<Option<StateKey>>()).max(1);
+        TranspositionTable { slots: vec![None; count] }
+    }
+
+    pub fn contains(&self, key: &StateKey) -> bool {
+        self.slots[self.slot(key)].as_ref() == Some(key)
+    }
+
+    pub fn insert(&mut self, key: StateKey) {
+        let slot = self.slot(&key);
+        self.slots[slot] = Some(key);
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.fill(None);
+    }
+
+    fn slot(&self, key: &StateKey) -> usize {
+        (key.hash() % self.slots.len() as u64) as usize
+    }
+}
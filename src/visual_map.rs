@@ -1,37 +1,141 @@
+use std::fmt;
 use std::fmt::Write;
-use std::{fmt, str};
+
+use serde_json::json;
 
 use crate::{Position, Tetrimino};
 
+/// The side of a tile in the SVG drawings, in pixels.
+const SVG_TILE: usize = 32;
+
+const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+
+#[derive(Debug, Clone)]
 pub struct VisualMap {
     tetriminos: Vec<(Tetrimino, Position)>,
+    // The index of each tetrimino in the input, which gives its letter.
+    indices: Vec<usize>,
     size: usize,
+    empty: char,
 }
 
 impl VisualMap {
     pub fn new(tetriminos: Vec<(Tetrimino, Position)>, size: usize) -> VisualMap {
-        VisualMap { tetriminos, size }
+        let indices = (0..tetriminos.len()).collect();
+        VisualMap { tetriminos, indices, size, empty: '.' }
     }
-}
 
-impl fmt::Display for VisualMap {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut map = vec![b'.'; self.size * self.size];
+    /// Names the tetriminos with the letters of these indices in the input instead of
+    /// their order, when the map only contains some of the tetriminos.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not one index per tetrimino.
+    pub fn with_indices(mut self, indices: Vec<usize>) -> VisualMap {
+        assert_eq!(indices.len(), self.tetriminos.len());
+        self.indices = indices;
+        self
+    }
+
+    /// Displays the empty tiles with this character instead of `.`.
+    pub fn with_empty(mut self, empty: char) -> VisualMap {
+        self.empty = empty;
+        self
+    }
+
+    /// The size of a side of the map.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The tetriminos in order along with the position where they are written.
+    pub fn tetriminos(&self) -> &[(Tetrimino, Position)] {
+        &self.tetriminos
+    }
+
+    /// The index in the input of every tetrimino.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
 
-        for ((t, p), c) in self.tetriminos.iter().zip(b'A'..) {
+    fn letter(&self, i: usize) -> char {
+        (b'A' + self.indices[i] as u8) as char
+    }
+
+    /// Writes the size of the map and the position of every tetrimino.
+    ///
+    /// ```text
+    /// {"size":4,"placements":[{"letter":"A","tetrimino":"L180","row":0,"col":2}]}
+    /// ```
+    pub fn to_json(&self) -> String {
+        let placements: Vec<_> = self
+            .tetriminos
+            .iter()
+            .enumerate()
+            .map(|(i, (t, p))| {
+                let c = self.letter(i);
+                json!({ "letter": c, "tetrimino": t.name(), "row": p.row, "col": p.col })
+            })
+            .collect();
+        json!({ "size": self.size, "placements": placements }).to_string()
+    }
+
+    /// Draws the map, every tetrimino has its own color and letter.
+    pub fn to_svg(&self) -> String {
+        let side = self.size * SVG_TILE;
+        let mut svg = String::new();
+        svg += &format!(r#"<svg xmlns="{}" width="{1}" height="{1}">"#, SVG_NAMESPACE, side);
+        svg += "\n<style>text{font-family:monospace;text-anchor:middle;dominant-baseline:central}";
+        svg += "</style>\n";
+        svg += &format!(r##"<rect width="{0}" height="{0}" fill="#eee"/>"##, side);
+        svg += "\n";
+
+        for (i, tile) in self.grid().iter().enumerate() {
+            if let Some(t) = tile {
+                let (x, y) = (i % self.size * SVG_TILE, i / self.size * SVG_TILE);
+                let fill = format!("hsl({}, 70%, 60%)", self.indices[*t] * 137 % 360);
+                svg += &format!(
+                    r#"<rect x="{}" y="{}" width="{2}" height="{2}" fill="{3}" stroke="white"/>"#,
+                    x, y, SVG_TILE, fill
+                );
+                let (x, y, letter) = (x + SVG_TILE / 2, y + SVG_TILE / 2, self.letter(*t));
+                svg += &format!(r#"<text x="{}" y="{}">{}</text>"#, x, y, letter);
+                svg += "\n";
+            }
+        }
+
+        svg += "</svg>\n";
+        svg
+    }
+
+    /// The index of the tetrimino covering each tile, row by row.
+    fn grid(&self) -> Vec<Option<usize>> {
+        let mut grid = vec![None; self.size * self.size];
+        for (i, (t, p)) in self.tetriminos.iter().enumerate() {
             let tetrimino_map = t.boolean_map();
-            for (line, tline) in map.chunks_mut(self.size).skip(p.row).zip(&tetrimino_map) {
+            for (line, tline) in grid.chunks_mut(self.size).skip(p.row).zip(&tetrimino_map) {
                 for (tile, full) in line.iter_mut().skip(p.col).zip(tline) {
                     if *full {
-                        *tile = c
+                        *tile = Some(i)
                     }
                 }
             }
         }
+        grid
+    }
+}
+
+impl fmt::Display for VisualMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.size == 0 {
+            return Ok(());
+        }
 
-        for line in map.chunks(self.size) {
-            let line = str::from_utf8(line).unwrap();
-            f.write_str(line)?;
+        let grid = self.grid();
+        for line in grid.chunks(self.size) {
+            line.iter()
+                .map(|t| t.map_or(self.empty, |t| self.letter(t)))
+                .try_for_each(|c| f.write_char(c))?;
             f.write_char('\n')?;
         }
 
@@ -0,0 +1,42 @@
+use js_sys::JSON;
+use wasm_bindgen::prelude::*;
+
+use crate::{find_best_fit, to_json, Format, ParseOptions, Tetrimino, VisualMap};
+
+/// Reads the tetriminos in any format `Format::detect` recognizes.
+fn read(text: &str) -> Result<Vec<Tetrimino>, JsError> {
+    let options = ParseOptions::default();
+    let format = Format::detect(text, &options);
+    format.parse(text, &options).map_err(|e| JsError::new(&format!("{:#}", e)))
+}
+
+fn read_and_solve(text: &str) -> Result<VisualMap, JsError> {
+    Ok(find_best_fit(&read(text)?))
+}
+
+/// Parses the tetriminos and returns the array of their names.
+#[wasm_bindgen]
+pub fn parse(text: &str) -> Result<JsValue, JsError> {
+    let tetriminos = read(text)?;
+    Ok(JSON::parse(&to_json(&tetriminos)).expect("valid JSON"))
+}
+
+/// Solves the tetriminos and returns the size of the map along with
+/// the letter, name, row and column of every tetrimino.
+#[wasm_bindgen]
+pub fn solve(text: &str) -> Result<JsValue, JsError> {
+    let map = read_and_solve(text)?;
+    Ok(JSON::parse(&map.to_json()).expect("valid JSON"))
+}
+
+/// Solves the tetriminos and draws the map with letters, like the command line.
+#[wasm_bindgen]
+pub fn render(text: &str) -> Result<String, JsError> {
+    Ok(read_and_solve(text)?.to_string())
+}
+
+/// Solves the tetriminos and draws the map in SVG.
+#[wasm_bindgen(js_name = renderSvg)]
+pub fn render_svg(text: &str) -> Result<String, JsError> {
+    Ok(read_and_solve(text)?.to_svg())
+}
@@ -0,0 +1,349 @@
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::process;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::{cursor, execute, queue, terminal};
+use enum_ordinalize::Ordinalize;
+use fillit::{
+    watch_best_fit, Position, SearchOptions, SearchOrder, StepControl, StepEvent, Tetrimino,
+    VisualMap,
+};
+
+use crate::{letter, parse_search_arg, Input};
+
+/// The delays between two steps, from the slowest speed to the fastest one.
+const DELAYS: [Duration; 6] = [
+    Duration::from_millis(1000),
+    Duration::from_millis(250),
+    Duration::from_millis(60),
+    Duration::from_millis(15),
+    Duration::from_millis(1),
+    Duration::ZERO,
+];
+
+/// The fastest speeds only redraw the map and read the keys this often.
+const FRAME: Duration = Duration::from_millis(30);
+
+/// The number of map events shown under the map.
+const LOG_LINES: usize = 6;
+
+const COLORS: [Color; 12] = [
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::DarkRed,
+    Color::DarkGreen,
+    Color::DarkYellow,
+    Color::DarkBlue,
+    Color::DarkMagenta,
+    Color::DarkCyan,
+];
+
+const HELP: &str = "space: pause  n: step  +/-: speed  s: next size  q: quit";
+
+/// Animates the backtracking search in the terminal.
+pub fn watch(mut args: impl Iterator<Item = String>) -> anyhow::Result<()> {
+    let mut input = Input::default();
+    let mut options = SearchOptions::default();
+    let mut speed = 2;
+    while let Some(arg) = args.next() {
+        if input.parse_arg(&arg, &mut args)? || parse_search_arg(&mut options, &arg, &mut args)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--speed" => {
+                let s: usize = args.next().context("missing speed")?.parse()?;
+                if !(1..=DELAYS.len()).contains(&s) {
+                    bail!("the speed must be between 1 and {}", DELAYS.len());
+                }
+                speed = s - 1;
+            }
+            otherwise => bail!("unknown argument {}", otherwise),
+        }
+    }
+
+    let paths = input.paths();
+    if paths.len() > 1 {
+        bail!("only one map can be watched at a time");
+    }
+    if options.order != SearchOrder::Lexicographic {
+        bail!("only the lexicographic search can be watched, --order cannot be used");
+    }
+    let tetriminos = input.read(paths[0])?;
+
+    let guard = TerminalGuard::enter()?;
+    let mut watch = Watch::new(tetriminos.clone(), speed);
+    let mut hook = |event, farthest: &[Position]| match watch.step(event, farthest) {
+        Ok(control) => control,
+        Err(e) => quit(Err(e)),
+    };
+    let (map, _stats) = watch_best_fit(&tetriminos, &options, &mut hook)?;
+
+    watch.finish(&map)?;
+    drop(guard);
+    print!("{}", map);
+    Ok(())
+}
+
+/// Puts the terminal in raw mode on an alternate screen, until it is dropped.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<TerminalGuard> {
+        terminal::enable_raw_mode()?;
+        let guard = TerminalGuard;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore_terminal();
+    }
+}
+
+fn restore_terminal() -> io::Result<()> {
+    execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()
+}
+
+/// Stops in the middle of the search, the hook cannot return an error.
+fn quit(result: io::Result<()>) -> ! {
+    let _ = restore_terminal();
+    match result {
+        Ok(()) => process::exit(0),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1)
+        }
+    }
+}
+
+/// What the search looks like after the last step.
+struct Watch {
+    tetriminos: Vec<Tetrimino>,
+    placed: Vec<Option<Position>>,
+    size: usize,
+    // The index of the last tetrimino written or removed.
+    index: usize,
+    steps: u64,
+    log: VecDeque<String>,
+    paused: bool,
+    speed: usize,
+    last_draw: Option<Instant>,
+}
+
+impl Watch {
+    fn new(tetriminos: Vec<Tetrimino>, speed: usize) -> Watch {
+        Watch {
+            placed: vec![None; tetriminos.len()],
+            tetriminos,
+            size: 0,
+            index: 0,
+            steps: 0,
+            log: VecDeque::with_capacity(LOG_LINES),
+            paused: false,
+            speed,
+            last_draw: None,
+        }
+    }
+
+    fn push_log(&mut self, line: String) {
+        if self.log.len() == LOG_LINES {
+            self.log.pop_front();
+        }
+        self.log.push_back(line);
+    }
+
+    /// Applies the event, draws the map and waits as long as the speed or the pause says.
+    fn step(&mut self, event: StepEvent, farthest: &[Position]) -> io::Result<StepControl> {
+        self.steps += 1;
+        match event {
+            StepEvent::NewMap { size } => {
+                self.size = size;
+                self.placed.iter_mut().for_each(|p| *p = None);
+                self.push_log(format!("trying a {}x{} map", size, size));
+            }
+            StepEvent::Place { index, position } => {
+                self.index = index;
+                self.placed[index] = Some(position);
+            }
+            StepEvent::Prune { index, .. } => self.index = index,
+            StepEvent::Remove { index, .. } => {
+                self.index = index;
+                self.placed[index] = None;
+            }
+            StepEvent::NeedNewMap { index, wasted, wastable } => {
+                let letter = letter(index);
+                self.push_log(format!(
+                    "{}: {} tiles wasted but only {} can be, the map is too small",
+                    letter, wasted, wastable
+                ));
+            }
+        }
+
+        let delay = DELAYS[self.speed];
+        let drawn_recently = self.last_draw.is_some_and(|d| d.elapsed() < FRAME);
+        if !self.paused && delay.is_zero() && drawn_recently {
+            return Ok(StepControl::Proceed);
+        }
+
+        let start = Instant::now();
+        self.draw(&event, farthest)?;
+        loop {
+            let key = if self.paused {
+                Some(event::read()?)
+            } else {
+                let left = delay.saturating_sub(start.elapsed());
+                if event::poll(left)? {
+                    Some(event::read()?)
+                } else {
+                    None
+                }
+            };
+
+            let key = match key {
+                None => return Ok(StepControl::Proceed),
+                Some(Event::Key(key)) if key.kind == KeyEventKind::Press => key,
+                Some(_) => continue,
+            };
+
+            match key.code {
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => quit(Ok(())),
+                KeyCode::Char('q') | KeyCode::Esc => quit(Ok(())),
+                KeyCode::Char(' ') => self.paused = !self.paused,
+                KeyCode::Char('n') | KeyCode::Right if self.paused => {
+                    return Ok(StepControl::Proceed)
+                }
+                KeyCode::Char('+') | KeyCode::Up => {
+                    self.speed = (self.speed + 1).min(DELAYS.len() - 1)
+                }
+                KeyCode::Char('-') | KeyCode::Down => self.speed = self.speed.saturating_sub(1),
+                KeyCode::Char('s') => {
+                    self.push_log(format!("skipping the {}x{} map", self.size, self.size));
+                    return Ok(StepControl::NextSize);
+                }
+                _ => continue,
+            }
+            self.draw(&event, farthest)?;
+        }
+    }
+
+    /// Shows the answer until a key is pressed.
+    fn finish(&mut self, map: &VisualMap) -> io::Result<()> {
+        self.size = map.size();
+        for (placed, (_, position)) in self.placed.iter_mut().zip(map.tetriminos()) {
+            *placed = Some(*position);
+        }
+        self.paused = true;
+        let status = format!("solved in a {}x{} map, press a key to quit", self.size, self.size);
+        self.draw_with(&status, None)?;
+        loop {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self, event: &StepEvent, farthest: &[Position]) -> io::Result<()> {
+        let status = match event {
+            StepEvent::NewMap { .. } => "new map".to_string(),
+            StepEvent::Place { index, position } => {
+                format!("{} written at row {} col {}", letter(*index), position.row, position.col)
+            }
+            StepEvent::Prune { index, .. } => {
+                format!("{} leaves free tiles that cannot be covered", letter(*index))
+            }
+            StepEvent::Remove { index, .. } => format!("{} removed", letter(*index)),
+            StepEvent::NeedNewMap { .. } => "need a new map".to_string(),
+        };
+        self.draw_with(&status, Some(farthest))
+    }
+
+    fn draw_with(&mut self, status: &str, farthest: Option<&[Position]>) -> io::Result<()> {
+        self.last_draw = Some(Instant::now());
+        let mut out = io::stdout().lock();
+        queue!(out, terminal::Clear(terminal::ClearType::All), cursor::MoveTo(0, 0))?;
+
+        let speed =
+            if self.paused { "paused".to_string() } else { format!("speed {}", self.speed + 1) };
+        let current = self.tetriminos[self.index];
+        queue!(
+            out,
+            Print(format!(
+                "{} tetriminos in a {}x{} map, step {}, {}",
+                self.tetriminos.len(),
+                self.size,
+                self.size,
+                self.steps,
+                speed
+            )),
+            cursor::MoveTo(0, 1),
+            Print(format!("tetrimino {} ({}): {}", letter(self.index), current.name(), status)),
+        )?;
+
+        let mut grid = vec![None; self.size * self.size];
+        for (i, (t, position)) in self.tetriminos.iter().zip(&self.placed).enumerate() {
+            if let Some(position) = position {
+                for (row, line) in t.boolean_map().iter().enumerate() {
+                    for (col, _) in line.iter().enumerate().filter(|(_, full)| **full) {
+                        let (row, col) = (position.row + row, position.col + col);
+                        if row < self.size && col < self.size {
+                            grid[row * self.size + col] = Some(i);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut y = 3;
+        for line in grid.chunks(self.size.max(1)) {
+            queue!(out, cursor::MoveTo(2, y))?;
+            for cell in line {
+                match cell {
+                    Some(i) => queue!(
+                        out,
+                        SetBackgroundColor(COLORS[i % COLORS.len()]),
+                        SetForegroundColor(Color::Black),
+                        Print(format!("{} ", letter(*i))),
+                        ResetColor,
+                    )?,
+                    None => queue!(out, Print(". "))?,
+                }
+            }
+            y += 1;
+        }
+
+        // The farthest positions are only meaningful for the types of the input.
+        if let Some(farthest) = farthest {
+            let mut types: Vec<_> = self.tetriminos.clone();
+            types.sort_by_key(|t| t.ordinal());
+            types.dedup();
+            let x = 2 * self.size as u16 + 6;
+            queue!(out, cursor::MoveTo(x, 3), Print("farthest positions"))?;
+            for (t, y) in types.iter().zip(4..) {
+                let far = farthest[t.ordinal()];
+                let line = format!("{:<5} row {:>2} col {:>2}", t.name(), far.row, far.col);
+                queue!(out, cursor::MoveTo(x, y), Print(line))?;
+            }
+        }
+
+        y += 1;
+        for line in &self.log {
+            queue!(out, cursor::MoveTo(0, y), Print(line))?;
+            y += 1;
+        }
+        queue!(out, cursor::MoveTo(0, y + 1), Print(HELP))?;
+        out.flush()
+    }
+}
@@ -0,0 +1,20 @@
+use crate::{Objective, Position, Tetrimino};
+
+/// Minimises the sum of the costs of several objectives, each one multiplied by its weight.
+/// The sum saturates at `usize::MAX`, the weights must be small enough to keep it below.
+#[derive(Default)]
+pub struct Weighted {
+    pub objectives: Vec<(usize, Box<dyn Objective>)>,
+}
+
+impl Objective for Weighted {
+    fn cost(&self, size: usize, fit: &[(Tetrimino, Option<Position>)]) -> usize {
+        let costs =
+            self.objectives.iter().map(|(weight, o)| weight.saturating_mul(o.cost(size, fit)));
+        costs.fold(0, usize::saturating_add)
+    }
+
+    fn interchangeable(&self, a: usize, b: usize) -> bool {
+        self.objectives.iter().all(|(_, o)| o.interchangeable(a, b))
+    }
+}
@@ -0,0 +1,41 @@
+// Tests the WebAssembly exports built by `wasm/test.sh`.
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { readFileSync } from 'node:fs';
+import { createRequire } from 'node:module';
+
+const require = createRequire(import.meta.url);
+const fillit = require('../target/wasm/fillit.js');
+const read = (path) => readFileSync(new URL(`../tests/${path}`, import.meta.url), 'utf8');
+
+const input = read('valid_1.fillit');
+
+test('parse', () => {
+  assert.deepEqual(fillit.parse(input), ['L180', 'I90', 'J270', 'Z']);
+  assert.deepEqual(fillit.parse('["O", "T"]'), ['O', 'T']);
+});
+
+test('solve', () => {
+  const map = fillit.solve(input);
+  assert.equal(map.size, 4);
+  assert.deepEqual(map.placements[0], { letter: 'A', tetrimino: 'L180', row: 0, col: 2 });
+  assert.equal(map.placements.length, 4);
+});
+
+test('render', () => {
+  for (const name of ['valid_0', 'valid_1', 'valid_2', 'valid_3', 'valid_4', 'valid_each_piece']) {
+    assert.equal(fillit.render(read(`${name}.fillit`)), read(`answers/${name}.answer`), name);
+  }
+  assert.equal(fillit.render('I90\nI90\nI90\nI90\n'), 'AAAA\nBBBB\nCCCC\nDDDD\n');
+});
+
+test('renderSvg', () => {
+  const svg = fillit.renderSvg(input);
+  assert.ok(svg.startsWith('<svg '));
+  assert.equal(svg.match(/<text /g).length, 16);
+});
+
+test('errors', () => {
+  assert.throws(() => fillit.parse(read('not_valid_X.fillit')), /invalid tetrimino character/);
+  assert.throws(() => fillit.solve(''), /no tetriminos/);
+});
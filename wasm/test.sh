@@ -0,0 +1,8 @@
+#!/bin/sh
+# Builds the WebAssembly exports for node and runs their tests,
+# it needs the `wasm-bindgen` command of the same version as the crate.
+set -e
+
+cargo build --lib --release --target wasm32-unknown-unknown --no-default-features --features wasm
+wasm-bindgen --target nodejs --out-dir target/wasm target/wasm32-unknown-unknown/release/fillit.wasm
+node --test wasm/fillit.test.mjs